@@ -15,35 +15,402 @@
 
 use clap::Parser;
 
+use crate::output::OutputFormat;
+use crate::types::{BinaryMode, GroupBy, LineNumberStyle, PathStyle, SortKey};
+use crate::utils::{parse_duration, parse_size};
+use std::time::Duration;
+
 #[derive(Parser)]
 #[command(name = "repo-context")]
 #[command(about = "Package repository context for LLMs")]
 /// Main CLI structure for the application.
 pub struct Cli {
-    /// Target paths/files to process (required)
-    #[arg(help = "Files or directories to process", required = true)]
+    /// Target paths/files to process (required unless --files-from or --stdin is given)
+    #[arg(
+        help = "Files or directories to process (`-` reads them from stdin, `file:40-80` only lines 40-80)",
+        required_unless_present_any = ["files_from", "stdin"]
+    )]
     pub target_paths: Vec<String>,
 
-    /// Toggle Recursive file traversal
-    #[arg(short, long, default_value_t = true)] // NOTE: Haven't tested this yet
+    /// Read target paths from stdin, one per line (e.g. `git ls-files | repo-context --stdin`)
+    #[arg(long = "stdin")]
+    pub stdin: bool,
+
+    /// Package exactly the paths listed in LIST (one per line, relative to the current directory)
+    #[arg(long = "files-from", value_name = "LIST")]
+    pub files_from: Option<String>,
+
+    /// With --files-from, skip listed paths that do not exist instead of failing
+    #[arg(long = "ignore-missing", requires = "files_from")]
+    pub ignore_missing: bool,
+
+    /// Recurse into subdirectories (default)
+    #[arg(short, long, overrides_with = "no_recursive")]
     pub recursive: bool,
 
+    /// Only process the top level of each directory
+    #[arg(long = "no-recursive", overrides_with = "recursive")]
+    pub no_recursive: bool,
+
+    /// Package the current directory as is, without looking for a git repository
+    #[arg(long = "no-git")]
+    pub no_git: bool,
+
+    /// Accept target paths outside the repository root (rejected by default)
+    #[arg(long = "allow-outside-root")]
+    pub allow_outside_root: bool,
+
+    /// Print the effective configuration as JSON and exit
+    #[arg(long = "print-config")]
+    pub print_config: bool,
+
+    /// List the files that would be packaged with their sizes and line counts, then exit
+    /// without reading file content or writing any output
+    #[arg(long = "dry-run")]
+    pub dry_run: bool,
+
+    /// Write the output with file headers, sizes, line counts and the summary, but no file
+    /// content
+    #[arg(long = "metadata-only")]
+    pub metadata_only: bool,
+
+    /// Read defaults from this TOML file instead of ./.repo-context.toml (flags still win)
+    #[arg(long = "config", value_name = "FILE")]
+    pub config: Option<String>,
+
     /// Output file (default: stdout)
     #[arg(short, long)]
     pub output: Option<String>,
 
-    /// Exclude dir/file patterns
+    /// Output format: markdown, a standalone html page, or jsonl with one file entry per line
+    #[arg(long = "format", value_enum, default_value_t = OutputFormat::Markdown)]
+    pub format: OutputFormat,
+
+    /// Copy the output to the system clipboard instead (takes precedence over --output)
+    #[arg(long = "clipboard")]
+    pub clipboard: bool,
+
+    /// With --output, also print the output to stdout
+    #[arg(long = "tee")]
+    pub tee: bool,
+
+    /// Name the output file after a fingerprint of its contents (e.g. context-<fingerprint>.md)
+    #[arg(long = "content-addressed")]
+    pub content_addressed: bool,
+
+    /// Gzip the output file (e.g. context.md.gz)
+    #[arg(long = "gzip")]
+    pub gzip: bool,
+
+    /// Shift all generated headings down by N levels (capped at `######`), for embedding in other documents
+    #[arg(long = "heading-offset", value_name = "N", default_value_t = 0)]
+    pub heading_offset: usize,
+
+    /// Exclude dir/file patterns, matched relative to the target directory or the repo root
     #[arg(short = 'e', long = "exclude")]
     pub exclude: Option<Vec<String>>,
 
-    /// Include dir/file patterns
+    /// Exclude common test files and directories (adds the test preset to --exclude)
+    #[arg(long = "exclude-tests")]
+    pub exclude_tests: bool,
+
+    /// Exclude paths containing this plain substring (no glob syntax, repeatable)
+    #[arg(long = "exclude-contains", value_name = "SUBSTR")]
+    pub exclude_contains: Option<Vec<String>>,
+
+    /// Only include files with these extensions (comma-separated, e.g. rs,toml)
+    #[arg(long = "ext", value_name = "EXTS", value_delimiter = ',')]
+    pub ext: Vec<String>,
+
+    /// Exclude files with these extensions (comma-separated, e.g. log,tmp)
+    #[arg(long = "no-ext", value_name = "EXTS", value_delimiter = ',')]
+    pub no_ext: Vec<String>,
+
+    /// Only package files of the dominant language (by lines), plus Markdown docs
+    #[arg(long = "primary-lang-only")]
+    pub primary_lang_only: bool,
+
+    /// Recurse into git submodule working directories (skipped by default)
+    #[arg(long = "include-submodules")]
+    pub include_submodules: bool,
+
+    /// Follow symlinked directories (skipped by default), visiting each real directory once
+    #[arg(long = "follow-symlinks")]
+    pub follow_symlinks: bool,
+
+    /// Only embed content of files smaller than SIZE (e.g. 500KB, 2MB; default 1MB)
+    #[arg(long = "max-file-size", value_name = "SIZE", value_parser = parse_size)]
+    pub max_file_size: Option<u64>,
+
+    /// Leave files larger than SIZE out entirely, not even listing them
+    #[arg(long = "skip-larger-than", value_name = "SIZE", value_parser = parse_size)]
+    pub skip_larger_than: Option<u64>,
+
+    /// Stop discovery after N matching files, with a warning
+    #[arg(long = "max-files", value_name = "N")]
+    pub max_files: Option<usize>,
+
+    /// Truncate the generated output at SIZE (e.g. 10MB), ending it with a notice
+    #[arg(long = "max-output-size", value_name = "SIZE", value_parser = parse_size)]
+    pub max_output_size: Option<u64>,
+
+    /// Include dir/file patterns, matched relative to the target directory or the repo root
     #[arg(short = 'i', long = "include")]
     pub include: Option<Vec<String>>,
 
-    /// Only include files modified within the last 7 days
-    #[arg(long = "recent")]
-    pub recent: bool,
+    /// Only include recently modified files: within the last 7 days, or within DURATION (e.g. 30d, 12h)
+    #[arg(
+        long = "recent",
+        value_name = "DURATION",
+        num_args = 0..=1,
+        default_missing_value = "7d",
+        value_parser = parse_duration
+    )]
+    pub recent: Option<Duration>,
     /// Show line numbers in file content output
     #[arg(short = 'l', long = "line-numbers")]
     pub line_numbers: bool,
+
+    /// Separator style used with --line-numbers
+    #[arg(long = "line-number-style", value_enum, default_value_t = LineNumberStyle::Colon)]
+    pub line_number_style: LineNumberStyle,
+
+    /// Embed binary files below --max-file-size as base64 or hex instead of skipping them
+    #[arg(long = "binary", value_name = "MODE", value_enum, default_value_t = BinaryMode::Skip)]
+    pub binary: BinaryMode,
+
+    /// Render code as a skeleton: signatures and type declarations, with bodies replaced by `{ ... }`
+    #[arg(long = "skeleton")]
+    pub skeleton: bool,
+
+    /// Annotate files with the age of their last commit
+    #[arg(long = "age")]
+    pub age: bool,
+
+    /// Show the last commit that modified each file in its header (walks the history, so slower)
+    #[arg(long = "git-annotate")]
+    pub git_annotate: bool,
+
+    /// Expand tabs to N spaces in rendered content
+    #[arg(long = "expand-tabs", value_name = "N")]
+    pub expand_tabs: Option<usize>,
+
+    /// Only expand tabs in leading indentation (requires --expand-tabs)
+    #[arg(long = "tabs-leading-only", requires = "expand_tabs")]
+    pub tabs_leading_only: bool,
+
+    /// Truncate rendered lines longer than N characters, noting how many were cut
+    #[arg(long = "max-line-width", value_name = "N")]
+    pub max_line_width: Option<usize>,
+
+    /// Also attach the output as a git note on HEAD (default ref: refs/notes/repo-context)
+    #[arg(
+        long = "output-to-git-note",
+        value_name = "REF",
+        num_args = 0..=1,
+        default_missing_value = crate::git::DEFAULT_NOTES_REF
+    )]
+    pub output_to_git_note: Option<String>,
+
+    /// Show full content only for files changed since REF, summarizing unchanged files
+    #[arg(long = "focus-changes", value_name = "REF")]
+    pub focus_changes: Option<String>,
+
+    /// Treat whitespace-only modifications as unchanged when detecting changes
+    #[arg(long = "dedupe-whitespace-only-diff")]
+    pub dedupe_whitespace_only_diff: bool,
+
+    /// Show the content of byte-identical files only once, later copies point to the first
+    #[arg(long = "dedupe")]
+    pub dedupe: bool,
+
+    /// How paths are displayed in file headers and the tree
+    #[arg(long = "path-style", value_enum, default_value_t = PathStyle::Relative)]
+    pub path_style: PathStyle,
+
+    /// Produce diff-friendly output for committing: no absolute paths, modification times or
+    /// relative dates, LF line endings
+    #[arg(long = "stable")]
+    pub stable: bool,
+
+    /// Only package files whose SHA-256 content hash is listed in FILE
+    #[arg(long = "only-hashes", value_name = "FILE")]
+    pub only_hashes: Option<String>,
+
+    /// Show each file's size rank and percentile in its header
+    #[arg(long = "rank")]
+    pub rank: bool,
+
+    /// Write one output file per file extension into OUTDIR
+    #[arg(
+        long = "split-by-ext",
+        value_name = "OUTDIR",
+        conflicts_with = "output"
+    )]
+    pub split_by_ext: Option<String>,
+
+    /// Report line ending and trailing whitespace statistics in the summary
+    #[arg(long = "whitespace-stats")]
+    pub whitespace_stats: bool,
+
+    /// Omit content of files larger than K times the median file size
+    #[arg(long = "trim-outliers", value_name = "K")]
+    pub trim_outliers: Option<f64>,
+
+    /// Show an ASCII graph of the last N commits in the metadata
+    #[arg(long = "git-graph", value_name = "N")]
+    pub git_graph: Option<usize>,
+
+    /// List the last N commits (hash, author, date, subject) in the metadata
+    #[arg(long = "git-log", value_name = "N", default_value_t = 0)]
+    pub git_log: usize,
+
+    /// List uncommitted changes (modified, added, deleted, untracked) in the metadata
+    #[arg(long = "git-status")]
+    pub git_status: bool,
+
+    /// Also write the directory tree as JSON to FILE
+    #[arg(long = "tree-json", value_name = "FILE")]
+    pub tree_json: Option<String>,
+
+    /// Stop embedding file content once N lines have been rendered in total (files are still listed)
+    #[arg(long = "max-total-render-lines", value_name = "N")]
+    pub max_total_render_lines: Option<usize>,
+
+    /// Fit the output into a token budget, packing the smallest files first and listing the rest
+    #[arg(long = "max-tokens", value_name = "N")]
+    pub max_tokens: Option<u64>,
+
+    /// Replace path segments matching this regex with *** in headers and the tree (content is kept)
+    #[arg(long = "redact-path", value_name = "REGEX")]
+    pub redact_path: Option<String>,
+
+    /// Mask likely secrets in file content (AWS keys, private keys, token=... assignments)
+    #[arg(long = "redact")]
+    pub redact: bool,
+
+    /// List the dependencies declared in the root Cargo.toml in the summary
+    #[arg(long = "deps")]
+    pub deps: bool,
+
+    /// Package only files with staged changes, as staged in the index (unstaged edits are ignored)
+    #[arg(long = "staged")]
+    pub staged: bool,
+
+    /// Package a pull-request-style view of the changes between two refs (e.g. main..HEAD)
+    #[arg(long = "range", value_name = "BASE..HEAD")]
+    pub range: Option<String>,
+
+    /// Omit the content of large data files (.json/.yaml/.yml/.csv over 500 lines or 50 KB)
+    #[arg(long = "skip-data")]
+    pub skip_data: bool,
+
+    /// Print the size (bytes and lines) of the generated output to stderr
+    #[arg(long = "report-output-size")]
+    pub report_output_size: bool,
+
+    /// Add a short generated overview (size, primary language, key directories) after the header
+    #[arg(long = "overview")]
+    pub overview: bool,
+
+    /// Group files by directory and add a short summary after each directory's files
+    #[arg(long = "dir-summaries")]
+    pub dir_summaries: bool,
+
+    /// Group files into sections, e.g. by role (source, tests, docs, config, build)
+    #[arg(long = "group-by", value_enum)]
+    pub group_by: Option<GroupBy>,
+
+    /// Order files in the output by path, size, lines or modification time
+    #[arg(long = "sort", value_enum, default_value_t = SortKey::Path)]
+    pub sort: SortKey,
+
+    /// Sort in descending instead of ascending order
+    #[arg(long = "reverse")]
+    pub reverse: bool,
+
+    /// Only package files whose last commit was made by this author (name or email)
+    #[arg(long = "author", value_name = "NAME|EMAIL")]
+    pub author: Option<String>,
+
+    /// Only package files changed between REV (e.g. a tag or HEAD~5) and HEAD
+    #[arg(long = "changed-since", value_name = "REV")]
+    pub changed_since: Option<String>,
+
+    /// List TODO/FIXME/HACK/XXX markers with file and line in the summary
+    #[arg(long = "todos")]
+    pub todos: bool,
+
+    /// Custom marker for --todos, replacing the defaults (repeatable, case-insensitive)
+    #[arg(long = "todo-marker", value_name = "MARKER")]
+    pub todo_marker: Option<Vec<String>>,
+
+    /// Show at most N children per directory in the tree
+    #[arg(long = "tree-max-children", value_name = "N")]
+    pub tree_max_children: Option<usize>,
+
+    /// List at most N extensions in the language breakdown, rolling the rest into "Other"
+    /// (0 lists all)
+    #[arg(long = "lang-breakdown-limit", value_name = "N", default_value_t = 10)]
+    pub lang_breakdown_limit: usize,
+
+    /// Stop expanding directories in the tree below depth N (files are still packaged)
+    #[arg(long = "max-depth", value_name = "N")]
+    pub max_depth: Option<usize>,
+
+    /// Draw the tree with ASCII connectors (|--, `--) instead of Unicode box-drawing characters
+    #[arg(long = "ascii-tree")]
+    pub ascii_tree: bool,
+
+    /// Include an estimated reading time (200 words per minute) in the summary
+    #[arg(long = "reading-time")]
+    pub reading_time: bool,
+
+    /// Estimate per-file complexity by counting branch keywords and rank the most complex files
+    #[arg(long = "complexity")]
+    pub complexity: bool,
+
+    /// Skip empty and whitespace-only files
+    #[arg(long = "exclude-empty")]
+    pub exclude_empty: bool,
+
+    /// Skip text files with fewer than N lines (binary files are always kept)
+    #[arg(long = "min-lines", value_name = "N")]
+    pub min_lines: Option<u64>,
+
+    /// Include hidden files and directories (names starting with a dot), except .git
+    #[arg(long = "hidden")]
+    pub hidden: bool,
+
+    /// Read files on a single thread instead of all cores
+    #[arg(long = "single-threaded")]
+    pub single_threaded: bool,
+
+    /// Show discovery progress on stderr (only when stderr is a terminal)
+    #[arg(long = "progress")]
+    pub progress: bool,
+
+    /// Add YAML front matter (path, size, lines, language, binary) to each file section
+    #[arg(long = "front-matter")]
+    pub front_matter: bool,
+}
+
+/// Exclude patterns added by `--exclude-tests`
+pub const EXCLUDE_TESTS_PRESET: &[&str] = &[
+    "tests/**",
+    "test/**",
+    "**/tests/**",
+    "**/*_test.*",
+    "**/*_tests.*",
+    "**/*.test.*",
+    "**/*.spec.*",
+    "**/test_*.py",
+];
+
+impl Cli {
+    /// Whether directories should be traversed recursively; the last of
+    /// `--recursive`/`--no-recursive` wins and recursion is on by default
+    pub fn is_recursive(&self) -> bool {
+        !self.no_recursive
+    }
 }
@@ -0,0 +1,62 @@
+//===----------------------------------------------------------------------===//
+//
+// Copyright (c) 2025 David Rivera
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+//
+// SPDX-License-Identifier: MIT
+//
+//===----------------------------------------------------------------------===//
+//
+// Defaults loaded from a `.repo-context.toml` file, overridden by CLI flags.
+//===----------------------------------------------------------------------===//
+//
+
+use crate::utils::parse_duration;
+use serde::Deserialize;
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+
+/// File name looked up in the current directory when `--config` is not given
+pub const DEFAULT_CONFIG_FILE: &str = ".repo-context.toml";
+
+/// Settings read from a config file. Every key is optional; unknown keys are rejected so
+/// typos do not go unnoticed.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ConfigFile {
+    pub include_patterns: Option<Vec<String>>,
+    pub exclude_patterns: Option<Vec<String>>,
+    pub recursive: Option<bool>,
+    // Same syntax as `--recent`, e.g. "30d"
+    pub recent: Option<String>,
+    pub line_numbers: Option<bool>,
+    pub output: Option<String>,
+}
+
+impl ConfigFile {
+    /// Load the config file at `path`. With `required` unset a missing file yields the
+    /// empty default, so the lookup of `.repo-context.toml` is optional.
+    pub fn load(path: &Path, required: bool) -> Result<Self, Box<dyn std::error::Error>> {
+        if !required && !path.is_file() {
+            return Ok(Self::default());
+        }
+
+        let text = fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read config file {}: {}", path.display(), e))?;
+        let config: ConfigFile = toml::from_str(&text)
+            .map_err(|e| format!("Failed to parse config file {}: {}", path.display(), e))?;
+        // Validate eagerly so a bad value is reported against the file it came from
+        _ = config
+            .recent_window()
+            .map_err(|e| format!("{}: {}", path.display(), e))?;
+        Ok(config)
+    }
+
+    /// The `recent` window, if set
+    pub fn recent_window(&self) -> Result<Option<Duration>, String> {
+        self.recent.as_deref().map(parse_duration).transpose()
+    }
+}
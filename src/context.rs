@@ -14,14 +14,25 @@
 //===----------------------------------------------------------------------===//
 //
 
+use crate::error::ContextError;
+use crate::files::{decode_text, read_file_list, sort_entries};
 use crate::git;
+use crate::manifest;
 use crate::types::*;
+use crate::utils::{
+    count_lines, estimate_tokens, normalize_path, redact_content, redact_path, redaction_pattern,
+};
 use crate::TreeContext;
 use git2::Repository;
+use regex::Regex;
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::Path;
 
 #[derive(Debug, Clone)]
 pub struct ContextManager {
     pub config: Config,
+    // Populated by `build_context`
     pub context: Option<RepositoryContext>,
 }
 
@@ -33,37 +44,43 @@ impl ContextManager {
         }
     }
 
-    /// Discover the git repository from the configured root path
-    fn discover_repository(&self) -> Result<Repository, Box<dyn std::error::Error>> {
-        Repository::discover(&self.config.root_path).map_err(|e| {
-            format!(
-                "Failed to discover repository from {}: {}",
-                self.config.root_path, e
-            )
-            .into()
-        })
-    }
-
-    /// Build tree representation based on configuration
-    /// Returns tree string for either full repo or specific target paths
-    fn build_tree_representation(&self) -> Result<String, Box<dyn std::error::Error>> {
-        let mut tree_ctx = TreeContext::new(self.config.clone());
+    /// The repository context, once `build_context` has run
+    pub fn context(&self) -> Option<&RepositoryContext> {
+        self.context.as_ref()
+    }
 
-        let tree_str = if self.config.target_paths.is_empty() {
-            tree_ctx.build_tree_from_root()?.tree_str.clone()
-        } else {
-            tree_ctx.build_tree_from_targets()?.tree_str.clone()
-        };
+    /// Consume the manager and return its repository context, building it first if
+    /// `build_context` has not run yet. Lets library callers inspect the files and git
+    /// metadata without going through `OutputContext`.
+    pub fn into_context(mut self) -> Result<RepositoryContext, ContextError> {
+        if self.context.is_none() {
+            self.build_context()?;
+        }
+        Ok(self
+            .context
+            .take()
+            .expect("build_context populates the context"))
+    }
+
+    /// Discover the git repository from the configured root path
+    fn discover_repository(&self) -> Result<Repository, ContextError> {
+        Repository::discover(&self.config.root_path).map_err(ContextError::Git)
+    }
 
-        Ok(tree_str)
+    /// Build the tree representation from the files already in `file_ctx`, so the tree shows
+    /// the same files as the output and the directories are not walked a second time
+    fn build_tree_representation(
+        &self,
+        file_ctx: &FileContext,
+    ) -> Result<TreeContext, Box<dyn std::error::Error>> {
+        let mut tree_ctx = TreeContext::new(self.config.clone());
+        _ = tree_ctx.build_tree_from_entries(&file_ctx.file_entries)?;
+        Ok(tree_ctx)
     }
 
     /// Build the file context based on configuration
     /// Returns FileContext for either full repo or specific target paths
-    fn build_file_context(
-        &self,
-        repo_root: &str,
-    ) -> Result<FileContext, Box<dyn std::error::Error>> {
+    fn build_file_context(&self, repo_root: &str) -> Result<FileContext, ContextError> {
         if self.config.target_paths.is_empty() {
             // If no target paths specified, process the entire repo (for tests and compatibility)
             FileContext::from_root(self.config.clone(), repo_root)
@@ -73,22 +90,158 @@ impl ContextManager {
         }
     }
 
+    /// Narrow and annotate the file context with git history, then collect the git metadata
+    fn apply_git(
+        &self,
+        repo: &Repository,
+        file_ctx: &mut FileContext,
+    ) -> Result<GitInfo, Box<dyn std::error::Error>> {
+        if let Some(author) = &self.config.author {
+            filter_by_author(repo, author, file_ctx)?;
+        }
+        if let Some(rev) = &self.config.changed_since {
+            // Both sides are relative to the repo root, whichever target found the file
+            let changed = git::paths_changed_since(repo, rev)?;
+            file_ctx
+                .file_entries
+                .retain(|entry| changed.contains(&normalize_path(&entry.path)));
+        }
+        if self.config.show_file_age || self.config.git_annotate {
+            annotate_last_commits(repo, &self.config, file_ctx)?;
+        }
+        if let Some(rev) = &self.config.focus_changes {
+            annotate_changes(repo, rev, self.config.dedupe_whitespace_only_diff, file_ctx)?;
+        }
+
+        if self.config.staged {
+            package_staged(repo, file_ctx)?;
+        }
+
+        let range = match &self.config.range {
+            Some(range) => {
+                let range = git::range_diff(repo, range)?;
+                annotate_range(repo, &range, file_ctx);
+                Some(range)
+            }
+            None => None,
+        };
+
+        let mut git_info = git::extract_git_info(repo)?;
+        if let Some(limit) = self.config.git_graph {
+            git_info.commit_graph = git::commit_graph(repo, limit)?;
+        }
+        if self.config.git_log > 0 {
+            git_info.recent_commits = git::recent_commits(repo, self.config.git_log)?;
+        }
+        if self.config.git_status {
+            git_info.status = git::working_tree_status(repo)?;
+        }
+        git_info.range = range;
+
+        Ok(git_info)
+    }
+
+    /// Flags that were given but only work inside a git repository
+    fn git_only_options(&self) -> Vec<&'static str> {
+        let config = &self.config;
+        [
+            (config.author.is_some(), "--author"),
+            (config.changed_since.is_some(), "--changed-since"),
+            (config.show_file_age, "--age"),
+            (config.git_annotate, "--git-annotate"),
+            (config.focus_changes.is_some(), "--focus-changes"),
+            (config.staged, "--staged"),
+            (config.range.is_some(), "--range"),
+            (config.git_graph.is_some(), "--git-graph"),
+            (config.git_log > 0, "--git-log"),
+            (config.git_status, "--git-status"),
+        ]
+        .into_iter()
+        .filter_map(|(given, flag)| given.then_some(flag))
+        .collect()
+    }
+
     /// This is the heart of our implementation.
     /// Build the repository context by gathering information from git and the filesystem.
     /// This function initializes the context and populates it with relevant data.
     /// Now discovers repo from current working directory and processes specific target paths.
-    pub fn build_context(&mut self) -> Result<(), Box<dyn std::error::Error>> {
-        let repo = self.discover_repository()?;
-        let actual_repo_root = get_repo_root_path(&repo)?;
+    /// The result is stored in `context`, see `context()` and `into_context()`.
+    pub fn build_context(&mut self) -> Result<(), ContextError> {
+        if let Some(list) = &self.config.files_from {
+            let listed = read_file_list(list, &self.config.root_path, self.config.ignore_missing)?;
+            for path in listed {
+                if !self.config.target_paths.contains(&path) {
+                    self.config.target_paths.push(path);
+                }
+            }
+        }
+
+        if self.config.stable && self.config.path_style == PathStyle::Absolute {
+            eprintln!("Warning: --stable shows relative paths instead of absolute ones");
+            self.config.path_style = PathStyle::Relative;
+        }
+
+        // Without git the configured root is used as is and all git lookups are skipped
+        let repo = if self.config.use_git {
+            Some(self.discover_repository()?)
+        } else {
+            let ignored = self.git_only_options();
+            if !ignored.is_empty() {
+                eprintln!(
+                    "Warning: --no-git ignores options that need git: {}",
+                    ignored.join(", ")
+                );
+            }
+            None
+        };
+        let actual_repo_root = match &repo {
+            Some(repo) => get_repo_root_path(repo)?,
+            None if self.config.root_path.is_empty() => {
+                fs::canonicalize(".")?.to_string_lossy().to_string()
+            }
+            None => fs::canonicalize(&self.config.root_path)?
+                .to_string_lossy()
+                .to_string(),
+        };
+
+        let mut file_ctx = self.build_file_context(&actual_repo_root)?;
+        let mut git_info = match &repo {
+            Some(repo) => self.apply_git(repo, &mut file_ctx)?,
+            None => GitInfo::default(),
+        };
+
+        let dependencies = if self.config.deps {
+            manifest::cargo_dependencies(Path::new(&actual_repo_root))?
+        } else {
+            Vec::new()
+        };
+
+        if self.config.redact {
+            redact_secrets(&mut file_ctx);
+        }
+
+        // Last step before rendering, on the final line counts and before paths are redacted
+        sort_entries(
+            &mut file_ctx.file_entries,
+            self.config.sort,
+            self.config.sort_descending,
+        );
+        let tree_ctx = self.build_tree_representation(&file_ctx)?;
 
-        let file_ctx = self.build_file_context(&actual_repo_root)?;
-        let tree_repr = self.build_tree_representation()?;
+        let mut root_path = actual_repo_root;
+        if let Some(pattern) = &self.config.redact_path {
+            let pattern = redaction_pattern(pattern)?;
+            redact_paths(&pattern, &mut file_ctx, &mut git_info);
+            root_path = redact_path(&root_path, &pattern);
+        }
 
         self.context = Some(RepositoryContext {
-            root_path: actual_repo_root,
-            git_info: git::extract_git_info(&repo)?,
+            root_path,
+            git_info,
             file_ctx,
-            tree_repr,
+            tree_repr: tree_ctx.tree_str,
+            tree: tree_ctx.tree,
+            dependencies,
         });
 
         assert!(self.context.is_some());
@@ -97,6 +250,179 @@ impl ContextManager {
     }
 }
 
+/// Apply `--redact-path` to every path that ends up in the output. This runs last, once all
+/// filesystem and git lookups that need the real paths are done.
+fn redact_paths(pattern: &Regex, file_ctx: &mut FileContext, git_info: &mut GitInfo) {
+    for entry in &mut file_ctx.file_entries {
+        entry.path = redact_path(&entry.path, pattern);
+    }
+    for (path, _) in &mut file_ctx.skipped {
+        *path = redact_path(path, pattern);
+    }
+    if let Some(range) = &mut git_info.range {
+        for path in range
+            .added
+            .iter_mut()
+            .chain(range.removed.iter_mut())
+            .chain(range.modified.iter_mut())
+        {
+            *path = redact_path(path, pattern);
+        }
+    }
+    for submodule in &mut git_info.submodules {
+        submodule.path = redact_path(&submodule.path, pattern);
+    }
+    for (path, _) in &mut git_info.status {
+        *path = redact_path(path, pattern);
+    }
+}
+
+/// Apply `--redact` to the content of every file entry, counting the masked secrets. Runs
+/// after the staged and range lookups so it sees the content that gets rendered.
+fn redact_secrets(file_ctx: &mut FileContext) {
+    for entry in &mut file_ctx.file_entries {
+        let Some(content) = &entry.content else {
+            continue;
+        };
+        let (redacted, count) = redact_content(content);
+        if count == 0 {
+            continue;
+        }
+        entry.lines = count_lines(&redacted);
+        entry.tokens = estimate_tokens(&redacted);
+        entry.content = Some(redacted);
+        file_ctx.redactions += count;
+    }
+}
+
+/// Attach the last commit time (`--age`) and hash (`--git-annotate`) to every file entry,
+/// from a single history walk. Untracked files are left as `None`.
+fn annotate_last_commits(
+    repo: &Repository,
+    config: &Config,
+    file_ctx: &mut FileContext,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let paths: Vec<String> = file_ctx
+        .file_entries
+        .iter()
+        .map(|f| f.path.clone())
+        .collect();
+    let commits = git::last_commits(repo, &paths)?;
+
+    for entry in &mut file_ctx.file_entries {
+        let commit = commits.get(&normalize_path(&entry.path));
+        if config.show_file_age {
+            entry.last_commit_time = commit.map(|c| c.time);
+        }
+        if config.git_annotate {
+            entry.last_commit = commit.map(|c| c.hash.clone());
+        }
+    }
+
+    Ok(())
+}
+
+/// Keep only the files whose last commit was authored by `author` (matched against the
+/// name or email, case-insensitively). Untracked files have no author and are dropped.
+fn filter_by_author(
+    repo: &Repository,
+    author: &str,
+    file_ctx: &mut FileContext,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let paths: Vec<String> = file_ctx
+        .file_entries
+        .iter()
+        .map(|f| f.path.clone())
+        .collect();
+    let commits = git::last_commits(repo, &paths)?;
+
+    file_ctx.file_entries.retain(|entry| {
+        commits
+            .get(&normalize_path(&entry.path))
+            .is_some_and(|commit| {
+                commit.author.eq_ignore_ascii_case(author)
+                    || commit.email.eq_ignore_ascii_case(author)
+            })
+    });
+
+    Ok(())
+}
+
+/// Keep only files with staged changes and show their content as staged in the index,
+/// ignoring any unstaged edits in the working tree
+fn package_staged(
+    repo: &Repository,
+    file_ctx: &mut FileContext,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let staged = git::staged_paths(repo)?;
+    file_ctx
+        .file_entries
+        .retain(|entry| staged.contains(&normalize_path(&entry.path)));
+
+    for entry in &mut file_ctx.file_entries {
+        entry.changed = Some(true);
+        if entry.is_binary && entry.raw_bytes.is_none() {
+            continue;
+        }
+        if let Some(bytes) = git::index_blob(repo, &normalize_path(&entry.path)) {
+            replace_with_blob(entry, bytes);
+        }
+    }
+
+    Ok(())
+}
+
+/// Show `bytes`, the file as stored by git, in place of the working tree file. Size and hash
+/// always describe the blob, but content already left out (e.g. by `--max-file-size`) stays out.
+fn replace_with_blob(entry: &mut FileEntry, bytes: Vec<u8>) {
+    entry.size = bytes.len() as u64;
+    entry.content_hash = Some(format!("{:x}", Sha256::digest(&bytes)));
+    if entry.omitted_reason.is_some() {
+        return;
+    }
+    if entry.is_binary {
+        entry.raw_bytes = Some(bytes);
+        return;
+    }
+    let (content, lossy) = decode_text(bytes);
+    entry.content_lossy = lossy;
+    entry.lines = count_lines(&content);
+    entry.line_ending = LineEnding::detect(&content);
+    entry.tokens = estimate_tokens(&content);
+    entry.content = Some(content);
+}
+
+/// Flag every file entry as changed or unchanged relative to `rev`
+fn annotate_changes(
+    repo: &Repository,
+    rev: &str,
+    ignore_whitespace: bool,
+    file_ctx: &mut FileContext,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let changed = git::changed_files(repo, rev, ignore_whitespace)?;
+
+    for entry in &mut file_ctx.file_entries {
+        entry.changed = Some(changed.contains(&normalize_path(&entry.path)));
+    }
+
+    Ok(())
+}
+
+/// Flag files touched by the range as changed and show their content as of the head ref
+fn annotate_range(repo: &Repository, range: &RangeDiff, file_ctx: &mut FileContext) {
+    for entry in &mut file_ctx.file_entries {
+        let path = normalize_path(&entry.path);
+        let changed = range.added.contains(&path) || range.modified.contains(&path);
+        entry.changed = Some(changed);
+
+        if changed && !entry.is_binary {
+            if let Some(bytes) = git::file_blob_at(repo, &range.head, &path) {
+                replace_with_blob(entry, bytes);
+            }
+        }
+    }
+}
+
 /// The root path read from git2 links the .git folder. While this is useful for git operations,
 /// for our purposes we need the actual root path of the repository. So It's convenient for the user.
 fn get_repo_root_path(repo: &Repository) -> Result<String, Box<dyn std::error::Error>> {
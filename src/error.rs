@@ -0,0 +1,116 @@
+//===----------------------------------------------------------------------===//
+//
+// Copyright (c) 2025 David Rivera
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+//
+// SPDX-License-Identifier: MIT
+//
+//===----------------------------------------------------------------------===//
+//
+// Error type returned by the public entry points of the library, so callers
+// can match on the kind of failure instead of inspecting messages.
+//===----------------------------------------------------------------------===//
+//
+
+use std::fmt;
+use std::io;
+use std::path::PathBuf;
+
+#[derive(Debug)]
+pub enum ContextError {
+    /// Reading the file system failed
+    Io(io::Error),
+    /// A git operation failed, e.g. no repository was found
+    Git(git2::Error),
+    /// An include or exclude pattern is not a valid glob
+    Glob(globset::Error),
+    /// A target path does not live under the repository root
+    PathOutsideRoot(PathBuf),
+    /// The output file could not be created or written
+    OutputCreation { path: String, source: io::Error },
+    /// Any other failure, described by its message
+    Other(String),
+}
+
+impl fmt::Display for ContextError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ContextError::Io(e) => write!(f, "I/O error: {}", e),
+            ContextError::Git(e) => write!(f, "Git error: {}", e),
+            ContextError::Glob(e) => write!(f, "Invalid glob pattern: {}", e),
+            ContextError::PathOutsideRoot(path) => {
+                write!(f, "Path {} is outside the repository root", path.display())
+            }
+            ContextError::OutputCreation { path, source } => {
+                write!(f, "Failed to write output to {}: {}", path, source)
+            }
+            ContextError::Other(message) => f.write_str(message),
+        }
+    }
+}
+
+impl std::error::Error for ContextError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ContextError::Io(e) => Some(e),
+            ContextError::Git(e) => Some(e),
+            ContextError::Glob(e) => Some(e),
+            ContextError::OutputCreation { source, .. } => Some(source),
+            ContextError::PathOutsideRoot(_) | ContextError::Other(_) => None,
+        }
+    }
+}
+
+impl From<io::Error> for ContextError {
+    fn from(e: io::Error) -> Self {
+        ContextError::Io(e)
+    }
+}
+
+impl From<git2::Error> for ContextError {
+    fn from(e: git2::Error) -> Self {
+        ContextError::Git(e)
+    }
+}
+
+impl From<globset::Error> for ContextError {
+    fn from(e: globset::Error) -> Self {
+        ContextError::Glob(e)
+    }
+}
+
+impl From<String> for ContextError {
+    fn from(message: String) -> Self {
+        ContextError::Other(message)
+    }
+}
+
+impl From<&str> for ContextError {
+    fn from(message: &str) -> Self {
+        ContextError::Other(message.to_string())
+    }
+}
+
+/// Internal helpers still return boxed errors; recover the original kind where possible
+impl From<Box<dyn std::error::Error>> for ContextError {
+    fn from(e: Box<dyn std::error::Error>) -> Self {
+        let e = match e.downcast::<ContextError>() {
+            Ok(e) => return *e,
+            Err(e) => e,
+        };
+        let e = match e.downcast::<io::Error>() {
+            Ok(e) => return ContextError::Io(*e),
+            Err(e) => e,
+        };
+        let e = match e.downcast::<git2::Error>() {
+            Ok(e) => return ContextError::Git(*e),
+            Err(e) => e,
+        };
+        match e.downcast::<globset::Error>() {
+            Ok(e) => ContextError::Glob(*e),
+            Err(e) => ContextError::Other(e.to_string()),
+        }
+    }
+}
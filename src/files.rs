@@ -15,14 +15,25 @@
 //
 
 use globset::{Glob, GlobSetBuilder};
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::io::{BufRead, BufReader};
-use std::path::{Path, PathBuf};
+use std::path::{Component, Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::time::{Duration, SystemTime};
 
-use crate::types::{Config, FileContext, FileEntry};
+use crate::error::ContextError;
+use crate::types::{
+    BinaryMode, Config, Discovery, FileContext, FileEntry, LineEnding, ProgressEvent, SortKey,
+};
+use crate::utils::{
+    count_lines, estimate_tokens, estimate_tokens_from_size, extension_allowed, extension_key,
+    language_name, normalize_path, path_contains_any,
+};
 
-/// Count lines in a file efficiently without loading entire content into memory
+/// Count lines in a file efficiently without loading entire content into memory. Lines are
+/// split the same way as `count_lines`, for files whose content is not kept.
 // NOTE: I wonder how expensive would this be?
 fn get_file_lines(path: &Path) -> Result<u64, Box<dyn std::error::Error>> {
     let file = fs::File::open(path)?;
@@ -30,44 +41,74 @@ fn get_file_lines(path: &Path) -> Result<u64, Box<dyn std::error::Error>> {
     Ok(reader.lines().count() as u64)
 }
 
-/// Filter fn: Check if a file was modified within the last 7 days
-fn is_recently_modified(path: &Path) -> Result<bool, Box<dyn std::error::Error>> {
+/// Filter fn: Check if a file was modified within `window` (by default the last 7 days)
+fn is_recently_modified(path: &Path, window: Duration) -> Result<bool, Box<dyn std::error::Error>> {
     let metadata = fs::metadata(path)?;
     let modified_time = metadata.modified()?;
-    let now = SystemTime::now();
-    let seven_days_ago = now - Duration::from_secs(7 * 24 * 60 * 60);
+    let cutoff = SystemTime::now()
+        .checked_sub(window)
+        .unwrap_or(SystemTime::UNIX_EPOCH);
 
-    Ok(modified_time >= seven_days_ago)
+    Ok(modified_time >= cutoff)
 }
 
 impl FileContext {
     pub fn new(config: Config) -> Self {
         Self {
             file_entries: Vec::new(),
+            skipped_by_size: 0,
+            redactions: 0,
+            skipped: Vec::new(),
             config,
         }
     }
 
+    /// SHA-256 fingerprint of the packaged file set: every path together with its content
+    /// hash (or size, when the content was not hashed). Independent of discovery order.
+    pub fn fingerprint(&self) -> String {
+        let mut entries: Vec<String> = self
+            .file_entries
+            .iter()
+            .map(|f| match &f.content_hash {
+                Some(hash) => format!("{}\0{}", f.path, hash),
+                None => format!("{}\0size:{}", f.path, f.size),
+            })
+            .collect();
+        entries.sort();
+
+        let mut hasher = Sha256::new();
+        for entry in entries {
+            hasher.update(entry.as_bytes());
+            hasher.update(b"\n");
+        }
+        format!("{:x}", hasher.finalize())
+    }
+
     /// Create a new FileContext with files discovered from the given root path
-    pub fn from_root(config: Config, root_path: &str) -> Result<Self, Box<dyn std::error::Error>> {
-        let files = Self::discover_files(root_path, &config)?;
+    pub fn from_root(config: Config, root_path: &str) -> Result<Self, ContextError> {
+        let discovery = Self::discover_files(root_path, root_path, &config)?;
+        let mut files = apply_post_discovery_filters(discovery.entries, &config)?;
+        sort_by_path(&mut files);
         Ok(Self {
             file_entries: files,
+            skipped_by_size: discovery.skipped_by_size,
+            redactions: 0,
+            skipped: discovery.skipped,
             config,
         })
     }
 
     /// Create a new FileContext with files discovered from specific target paths
-    pub fn from_target_paths(
-        config: Config,
-        repo_root: &str,
-    ) -> Result<Self, Box<dyn std::error::Error>> {
+    pub fn from_target_paths(config: Config, repo_root: &str) -> Result<Self, ContextError> {
         let mut all_files = Vec::new();
+        let mut skipped_by_size = 0;
+        let mut skipped = Vec::new();
 
-        for target_path in &config.target_paths {
+        for target in &config.target_paths {
+            let (target_path, line_range) = split_line_range(target, &config.root_path)?;
             // Convert target path to absolute path if it's relative
             let abs_target_path = if Path::new(target_path).is_absolute() {
-                target_path.clone()
+                target_path.to_string()
             } else {
                 // Resolve relative to current working directory (config.root_path)
                 Path::new(&config.root_path)
@@ -79,9 +120,13 @@ impl FileContext {
             let target_path_obj = Path::new(&abs_target_path);
 
             if target_path_obj.is_file() {
+                // Paths are stored relative to the repo root, so the file has to resolve under it
+                let rel_path =
+                    path_within_root(target_path_obj, repo_root, config.allow_outside_root)?;
+
                 // Single file - check recent filter if enabled
                 if config.recent_only {
-                    match is_recently_modified(target_path_obj) {
+                    match is_recently_modified(target_path_obj, config.recent_window()) {
                         Ok(false) => continue, // File is not recent, skip
                         Err(e) => {
                             eprintln!(
@@ -94,91 +139,156 @@ impl FileContext {
                     }
                 }
 
+                // --max-files caps the files of all targets together
+                if config.max_files.is_some_and(|max| all_files.len() >= max) {
+                    eprintln!(
+                        "Warning: Skipped {} (--max-files limit reached)",
+                        abs_target_path
+                    );
+                    continue;
+                }
+
                 // Single file - create file entry directly
-                match create_file_entry(target_path_obj) {
+                let entry = create_file_entry(target_path_obj, &config).and_then(|mut entry| {
+                    if let Some(range) = line_range {
+                        select_lines(&mut entry, target_path_obj, range, &config)?;
+                    }
+                    Ok(entry)
+                });
+                match entry {
                     Ok(mut file_entry) => {
-                        // Make path relative to repo root for consistency
-                        if let Ok(rel_path) = target_path_obj.strip_prefix(repo_root) {
-                            file_entry.path = rel_path.to_string_lossy().to_string();
-                        }
+                        file_entry.path = normalize_path(&rel_path.to_string_lossy());
                         all_files.push(file_entry);
                     }
                     Err(e) => {
-                        eprintln!("Warning: Could not process file {}: {}", abs_target_path, e)
+                        eprintln!("Warning: Could not process file {}: {}", abs_target_path, e);
+                        skipped.push((normalize_path(&rel_path.to_string_lossy()), e.to_string()));
                     }
                 }
             } else if target_path_obj.is_dir() {
-                // Directory - discover files within it
-                let files = Self::discover_files(&abs_target_path, &config)?;
-                all_files.extend(files);
+                if line_range.is_some() {
+                    return Err(ContextError::Other(format!(
+                        "Line range in target {} needs a file, not a directory",
+                        target
+                    )));
+                }
+                let rel_dir =
+                    path_within_root(target_path_obj, repo_root, config.allow_outside_root)?;
+
+                // Directory - discover files within it. The walk names entries relative to the
+                // target, git lookups need them relative to the repo root like file targets
+                // The walk may only add what is left of the --max-files budget
+                let budget_config;
+                let target_config = match config.max_files {
+                    Some(max) => {
+                        budget_config = Config {
+                            max_files: Some(max.saturating_sub(all_files.len())),
+                            ..config.clone()
+                        };
+                        &budget_config
+                    }
+                    None => &config,
+                };
+                let discovery = Self::discover_files(&abs_target_path, repo_root, target_config)?;
+                let from_root = |path: &str| normalize_path(&rel_dir.join(path).to_string_lossy());
+                all_files.extend(discovery.entries.into_iter().map(|mut entry| {
+                    entry.path = from_root(&entry.path);
+                    entry
+                }));
+                skipped_by_size += discovery.skipped_by_size;
+                skipped.extend(
+                    discovery
+                        .skipped
+                        .into_iter()
+                        .map(|(path, reason)| (from_root(&path), reason)),
+                );
             } else {
                 eprintln!("Warning: Target path does not exist: {}", abs_target_path);
             }
         }
 
+        let mut all_files = apply_post_discovery_filters(all_files, &config)?;
+        sort_by_path(&mut all_files);
+        skipped.sort();
         Ok(Self {
             file_entries: all_files,
+            skipped_by_size,
+            redactions: 0,
+            skipped,
             config,
         })
     }
 
-    /// Discover files in the given root path
+    /// Discover files in the given root path, a directory inside `repo_root`. Include and
+    /// exclude globs match paths relative to either of the two. Besides the entries, the
+    /// result counts the files left out by `Config::skip_larger_than` and lists the files
+    /// that cannot be read together with the reason.
     pub fn discover_files(
         root_path: &str,
+        repo_root: &str,
         config: &Config,
-    ) -> Result<Vec<FileEntry>, Box<dyn std::error::Error>> {
-        let mut files = Vec::new();
-
-        // Build globsets for include and exclude patterns
-        let exclude_set = if config.exclude_patterns.is_empty() {
-            None
-        } else {
-            Some(build_globset(&config.exclude_patterns)?)
-        };
-
-        let include_set = if config.include_patterns.is_empty() {
-            None
-        } else {
-            Some(build_globset(&config.include_patterns)?)
-        };
+    ) -> Result<Discovery, ContextError> {
+        let mut paths = Vec::new();
+        let mut skipped_by_size = 0;
+        let globs = PathGlobs::new(config, repo_root)?;
+        let mut visited = HashSet::new();
+        _ = first_visit(&mut visited, Path::new(root_path));
 
         // Start traversal
-        Self::traverse_directory(
+        let limit_hit = Self::traverse_directory(
             root_path,
             Path::new(root_path),
             config,
-            &mut files,
-            &exclude_set,
-            &include_set,
+            &mut paths,
+            &mut skipped_by_size,
+            &globs,
+            &mut visited,
         )?;
+        if limit_hit {
+            eprintln!(
+                "Warning: Stopped discovery in {} after {} files (--max-files limit reached)",
+                root_path,
+                paths.len()
+            );
+        }
 
-        Ok(files)
+        // Read the eligible files only once the walk is done, so it can be spread across threads
+        paths.sort_by(|a, b| a.1.cmp(&b.1));
+        let (entries, skipped) = create_file_entries(paths, config);
+        Ok(Discovery {
+            entries,
+            skipped_by_size,
+            skipped,
+        })
     }
 
-    /// Recursively traverse directories to find files consider glob patterns (include/exclude)
+    /// Recursively traverse directories to find files consider glob patterns (include/exclude).
+    /// Collects `(absolute path, normalized relative path)` pairs of the files to package.
+    /// Returns true when the walk stopped early because `Config::max_files` was reached.
     fn traverse_directory(
         current_path_str: &str,
         root_path: &Path,
         config: &Config,
-        files: &mut Vec<FileEntry>,
-        exclude_set: &Option<globset::GlobSet>,
-        include_set: &Option<globset::GlobSet>,
-    ) -> Result<(), Box<dyn std::error::Error>> {
+        paths: &mut Vec<(PathBuf, String)>,
+        skipped_by_size: &mut usize,
+        globs: &PathGlobs,
+        visited: &mut HashSet<PathBuf>,
+    ) -> Result<bool, ContextError> {
         let current_path = Path::new(current_path_str);
 
         if !current_path.exists() || !current_path.is_dir() {
-            return Ok(());
+            return Ok(false);
         }
 
-        for entry in fs::read_dir(current_path)? {
-            let entry = entry?;
+        // Sorted so that a --max-files cut keeps the same files from run to run
+        let mut entries = fs::read_dir(current_path)?.collect::<Result<Vec<_>, _>>()?;
+        entries.sort_by_key(|e| e.file_name());
+
+        for entry in entries {
             let entry_path = entry.path();
 
-            // Skip hidden files and directories (starting with .)
-            if let Some(name) = entry_path.file_name() {
-                if name.to_string_lossy().starts_with('.') {
-                    continue;
-                }
+            if is_skipped_hidden(&entry_path, config.include_hidden) {
+                continue;
             }
 
             // Compute relative path (fallback to absolute if cannot strip)
@@ -188,24 +298,29 @@ impl FileContext {
             };
             let rel_str = rel_path.to_string_lossy();
 
+            // Plain substring excludes are cheaper than glob matching, check them first
+            if path_contains_any(&rel_str, &config.exclude_contains) {
+                continue;
+            }
+
             // Exclude patterns: if any match, skip
-            if let Some(exclude) = exclude_set {
-                if exclude.is_match(rel_str.as_ref()) {
-                    continue;
-                }
+            if globs.excludes(&entry_path, &rel_str) {
+                continue;
             }
 
             if entry_path.is_file() {
                 // Include patterns: if provided and none match, skip
-                if let Some(include) = include_set {
-                    if !include.is_match(rel_str.as_ref()) {
-                        continue;
-                    }
+                if !globs.includes(&entry_path, &rel_str) {
+                    continue;
+                }
+
+                if !extension_allowed(&rel_str, &config.include_exts, &config.exclude_exts) {
+                    continue;
                 }
 
                 // Recent filter: if enabled and file is not recently modified, skip
                 if config.recent_only {
-                    match is_recently_modified(&entry_path) {
+                    match is_recently_modified(&entry_path, config.recent_window()) {
                         Ok(false) => continue,
                         Err(e) => {
                             eprintln!(
@@ -219,35 +334,386 @@ impl FileContext {
                     }
                 }
 
-                match create_file_entry(&entry_path) {
-                    Ok(mut file_entry) => {
-                        // Store relative path for consistency
-                        file_entry.path = rel_str.to_string();
-                        files.push(file_entry)
-                    }
-                    Err(e) => eprintln!(
-                        "Warning: Could not process file {}: {}",
-                        entry_path.to_string_lossy(),
-                        e
-                    ),
+                if is_larger_than(&entry_path, config.skip_larger_than) {
+                    *skipped_by_size += 1;
+                    continue;
                 }
-            } else if entry_path.is_dir() && config.is_recursive {
-                Self::traverse_directory(
+
+                if config.max_files.is_some_and(|max| paths.len() >= max) {
+                    return Ok(true);
+                }
+
+                // Store relative path for consistency
+                let rel_str = normalize_path(&rel_str);
+                paths.push((entry_path, rel_str));
+                if let Some(progress) = &config.progress {
+                    progress.report(ProgressEvent::Discovered(paths.len()));
+                }
+            } else if entry_path.is_dir()
+                && config.is_recursive
+                && (config.include_submodules || !is_submodule_dir(&entry_path))
+                && enter_directory(&entry_path, config.follow_symlinks, visited)
+            {
+                let limit_hit = Self::traverse_directory(
                     &entry_path.to_string_lossy(),
                     root_path,
                     config,
-                    files,
-                    exclude_set,
-                    include_set,
+                    paths,
+                    skipped_by_size,
+                    globs,
+                    visited,
                 )?;
+                if limit_hit {
+                    return Ok(true);
+                }
             }
         }
 
-        Ok(())
+        Ok(false)
+    }
+}
+
+/// Name of the tool-specific ignore file looked up in the root directory
+pub const IGNORE_FILE: &str = ".repo-context-ignore";
+
+/// Read the glob lines of a `.repo-context-ignore` file in `root`. Blank lines and `#`
+/// comments are skipped and a trailing `/` is dropped, so `target/` excludes the directory.
+/// A missing file yields no patterns.
+pub fn load_ignore_file(root: &Path) -> Result<Vec<String>, ContextError> {
+    let path = root.join(IGNORE_FILE);
+    if !path.is_file() {
+        return Ok(Vec::new());
+    }
+
+    let text = fs::read_to_string(&path)
+        .map_err(|e| ContextError::Other(format!("Failed to read {}: {}", path.display(), e)))?;
+    Ok(text
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| line.trim_end_matches('/').to_string())
+        .filter(|line| !line.is_empty())
+        .collect())
+}
+
+/// `Config::exclude_patterns` together with the lines of the ignore file in the root directory
+fn exclude_patterns_with_ignore_file(config: &Config) -> Result<Vec<String>, ContextError> {
+    let mut patterns = config.exclude_patterns.clone();
+    patterns.extend(load_ignore_file(Path::new(&config.root_path))?);
+    Ok(patterns)
+}
+
+/// Order entries by relative path, so the output does not depend on `fs::read_dir` order or
+/// on the order targets were given in
+fn sort_by_path(entries: &mut [FileEntry]) {
+    entries.sort_by(|a, b| a.path.cmp(&b.path));
+}
+
+/// Order entries by `key` (`--sort`), descending with `descending` (`--reverse`). Ties are
+/// broken by ascending path either way, so equal keys keep a stable order.
+pub fn sort_entries(entries: &mut [FileEntry], key: SortKey, descending: bool) {
+    entries.sort_by(|a, b| {
+        let by_key = match key {
+            SortKey::Path => a.path.cmp(&b.path),
+            SortKey::Size => a.size.cmp(&b.size),
+            SortKey::Lines => a.lines.cmp(&b.lines),
+            SortKey::Modified => a.modified.cmp(&b.modified),
+        };
+        let by_key = if descending { by_key.reverse() } else { by_key };
+        by_key.then_with(|| a.path.cmp(&b.path))
+    });
+}
+
+/// Read a newline-separated list of paths (relative to `root_path`; blank lines and `#`
+/// comments are ignored). Paths that do not exist are an error unless `ignore_missing`
+/// is set, in which case they are skipped with a warning.
+pub fn read_file_list(
+    list_path: &str,
+    root_path: &str,
+    ignore_missing: bool,
+) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+    let listing = fs::read_to_string(list_path)
+        .map_err(|e| format!("Failed to read file list {}: {}", list_path, e))?;
+
+    let (existing, missing): (Vec<String>, Vec<String>) = listing
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(normalize_path)
+        .partition(|path| Path::new(root_path).join(path).exists());
+
+    if !missing.is_empty() {
+        if !ignore_missing {
+            return Err(format!(
+                "Paths listed in {} do not exist: {}",
+                list_path,
+                missing.join(", ")
+            )
+            .into());
+        }
+        for path in &missing {
+            eprintln!("Warning: Listed path does not exist, skipping: {}", path);
+        }
+    }
+
+    Ok(existing)
+}
+
+/// Read newline-separated target paths, e.g. piped from `git ls-files`. Lines are trimmed and
+/// blank ones skipped.
+pub fn read_target_paths<R: BufRead>(reader: R) -> std::io::Result<Vec<String>> {
+    let mut paths = Vec::new();
+    for line in reader.lines() {
+        let line = line?;
+        let path = line.trim();
+        if !path.is_empty() {
+            paths.push(path.to_string());
+        }
+    }
+    Ok(paths)
+}
+
+/// Filters that need the fully discovered file set (or loaded content) before they can run
+fn apply_post_discovery_filters(
+    files: Vec<FileEntry>,
+    config: &Config,
+) -> Result<Vec<FileEntry>, Box<dyn std::error::Error>> {
+    let mut files = files;
+
+    if let Some(hash_file) = &config.only_hashes {
+        files = filter_by_hashes(files, hash_file)?;
+    }
+
+    if config.exclude_empty {
+        files.retain(|f| !is_blank_entry(f));
+    }
+
+    // Binary files have no line count and are always kept
+    if let Some(min_lines) = config.min_lines {
+        files.retain(|f| f.is_binary || f.lines >= min_lines);
+    }
+
+    if config.primary_lang_only {
+        retain_primary_language(&mut files);
+    }
+
+    if config.skip_data {
+        skip_large_data_files(&mut files);
+    }
+
+    if let Some(factor) = config.trim_outliers {
+        trim_outliers(&mut files, factor);
+    }
+
+    // Content loaded for a content filter above is not kept for a metadata-only output
+    if config.metadata_only {
+        for file in &mut files {
+            file.content = None;
+            file.raw_bytes = None;
+        }
+    }
+
+    Ok(files)
+}
+
+/// Keep only files of the dominant language (by lines), plus Markdown docs.
+/// Files with an unrecognized extension never count as the dominant language.
+fn retain_primary_language(files: &mut Vec<FileEntry>) {
+    let mut lines_by_language: HashMap<&'static str, u64> = HashMap::new();
+    for file in files.iter() {
+        if let Some(language) = language_name(&extension_key(&file.path)) {
+            *lines_by_language.entry(language).or_default() += file.lines;
+        }
+    }
+    let Some(primary) = lines_by_language
+        .into_iter()
+        .max_by(|a, b| a.1.cmp(&b.1).then(b.0.cmp(a.0)))
+        .map(|(language, _)| language)
+    else {
+        return;
+    };
+
+    files.retain(|f| match language_name(&extension_key(&f.path)) {
+        Some(language) => language == primary || language == "Markdown",
+        None => false,
+    });
+}
+
+/// Zero-length files, and text files containing only whitespace
+fn is_blank_entry(file: &FileEntry) -> bool {
+    file.size == 0
+        || file
+            .content
+            .as_deref()
+            .is_some_and(|content| content.trim().is_empty())
+}
+
+/// Whether the file at `path` is larger than `limit` bytes; false without a limit
+pub(crate) fn is_larger_than(path: &Path, limit: Option<u64>) -> bool {
+    limit.is_some_and(|limit| fs::metadata(path).is_ok_and(|m| m.len() > limit))
+}
+
+/// Path of the target `path` relative to `repo_root`, with symlinks and `..` resolved to
+/// decide whether it really lies inside the root. Paths resolving elsewhere are rejected with
+/// `ContextError::PathOutsideRoot`, or kept as their canonical absolute path when
+/// `allow_outside` is set (`--allow-outside-root`).
+fn path_within_root(
+    path: &Path,
+    repo_root: &str,
+    allow_outside: bool,
+) -> Result<PathBuf, ContextError> {
+    let canonical_path = path.canonicalize()?;
+    let canonical_root = Path::new(repo_root).canonicalize()?;
+
+    let Ok(canonical_rel) = canonical_path.strip_prefix(&canonical_root) else {
+        return if allow_outside {
+            Ok(canonical_path)
+        } else {
+            Err(ContextError::PathOutsideRoot(path.to_path_buf()))
+        };
+    };
+
+    // Keep the path as given (e.g. through a symlinked directory) when it names the file
+    // directly, otherwise fall back to the resolved one
+    match path.strip_prefix(repo_root) {
+        Ok(rel) if !rel.components().any(|c| c == Component::ParentDir) => Ok(rel.to_path_buf()),
+        _ => Ok(canonical_rel.to_path_buf()),
+    }
+}
+
+/// Record the canonical location of `dir` in `visited`. False when it was already there,
+/// which is how symlink cycles are broken.
+pub(crate) fn first_visit(visited: &mut HashSet<PathBuf>, dir: &Path) -> bool {
+    fs::canonicalize(dir).is_ok_and(|real| visited.insert(real))
+}
+
+/// Whether to recurse into the directory `dir`. Symlinked directories are only entered with
+/// `follow_symlinks`, and then only if their real location has not been visited yet.
+pub(crate) fn enter_directory(
+    dir: &Path,
+    follow_symlinks: bool,
+    visited: &mut HashSet<PathBuf>,
+) -> bool {
+    let is_symlink = fs::symlink_metadata(dir).is_ok_and(|m| m.file_type().is_symlink());
+    if is_symlink && !follow_symlinks {
+        return false;
+    }
+    // Without following symlinks the walk cannot loop, so there is nothing to track
+    !follow_symlinks || first_visit(visited, dir)
+}
+
+/// Hidden entries (names starting with `.`) are skipped unless `include_hidden` is set.
+/// The `.git` directory itself is always skipped.
+pub(crate) fn is_skipped_hidden(path: &Path, include_hidden: bool) -> bool {
+    let Some(name) = path.file_name() else {
+        return false;
+    };
+    let name = name.to_string_lossy();
+    name == ".git" || (!include_hidden && name.starts_with('.'))
+}
+
+/// Checked-out submodules have a `.git` file (a gitlink) instead of a `.git` directory
+pub(crate) fn is_submodule_dir(path: &Path) -> bool {
+    path.join(".git").is_file()
+}
+
+/// Keep only files whose content hash appears in `hash_file`.
+/// The file holds one hash per line; anything after the hash (e.g. `sha256sum` paths) is ignored.
+fn filter_by_hashes(
+    files: Vec<FileEntry>,
+    hash_file: &str,
+) -> Result<Vec<FileEntry>, Box<dyn std::error::Error>> {
+    let listing = fs::read_to_string(hash_file)
+        .map_err(|e| format!("Failed to read hash list {}: {}", hash_file, e))?;
+    let wanted: HashSet<String> = listing
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter_map(|line| line.split_whitespace().next())
+        .map(|hash| hash.to_lowercase())
+        .collect();
+
+    let files: Vec<FileEntry> = files
+        .into_iter()
+        .filter(|f| {
+            f.content_hash
+                .as_ref()
+                .is_some_and(|hash| wanted.contains(hash))
+        })
+        .collect();
+
+    let matched: HashSet<&str> = files
+        .iter()
+        .filter_map(|f| f.content_hash.as_deref())
+        .collect();
+    for hash in &wanted {
+        if !matched.contains(hash.as_str()) {
+            eprintln!("Warning: No file matched content hash {}", hash);
+        }
+    }
+
+    Ok(files)
+}
+
+/// Drop the content of files whose size exceeds `factor` times the median file size
+fn trim_outliers(files: &mut [FileEntry], factor: f64) {
+    if files.is_empty() {
+        return;
+    }
+
+    let mut sizes: Vec<u64> = files.iter().map(|f| f.size).collect();
+    sizes.sort_unstable();
+    let mid = sizes.len() / 2;
+    let median = if sizes.len().is_multiple_of(2) {
+        (sizes[mid - 1] + sizes[mid]) as f64 / 2.0
+    } else {
+        sizes[mid] as f64
+    };
+    // With a zero median every non-empty file would count as an outlier
+    if median == 0.0 {
+        return;
+    }
+
+    let limit = factor * median;
+    for file in files.iter_mut() {
+        if file.size as f64 > limit && file.content.is_some() {
+            file.content = None;
+            file.omitted_reason = Some(format!(
+                "outlier, {} bytes exceeds {}x the median size ({} bytes)",
+                file.size, factor, median
+            ));
+        }
+    }
+}
+
+/// Structured data formats considered by `--skip-data`
+const DATA_EXTENSIONS: &[&str] = &["json", "yaml", "yml", "csv"];
+/// Data files above either threshold have their content omitted by `--skip-data`
+const DATA_MAX_LINES: u64 = 500;
+const DATA_MAX_BYTES: u64 = 50_000;
+
+/// Omit the content of large structured data files, which rarely help explain the code
+fn skip_large_data_files(files: &mut [FileEntry]) {
+    for file in files.iter_mut() {
+        let is_data = Path::new(&file.path)
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .is_some_and(|ext| DATA_EXTENSIONS.contains(&ext.to_lowercase().as_str()));
+        if !is_data || file.content.is_none() {
+            continue;
+        }
+
+        if file.lines > DATA_MAX_LINES || file.size > DATA_MAX_BYTES {
+            file.content = None;
+            file.omitted_reason = Some(format!(
+                "large data file ({} lines, {} bytes)",
+                file.lines, file.size
+            ));
+        }
     }
 }
 
-fn build_globset(patterns: &[String]) -> Result<globset::GlobSet, Box<dyn std::error::Error>> {
+fn build_globset(patterns: &[String]) -> Result<globset::GlobSet, ContextError> {
     let mut builder = GlobSetBuilder::new();
 
     for pattern in patterns {
@@ -258,47 +724,362 @@ fn build_globset(patterns: &[String]) -> Result<globset::GlobSet, Box<dyn std::e
     Ok(builder.build()?)
 }
 
-fn create_file_entry(path: &Path) -> Result<FileEntry, Box<dyn std::error::Error>> {
+/// Include and exclude globs of a discovery run. A path is tested relative to the directory
+/// being discovered and relative to the repository root, and a match on either counts, so
+/// `*.rs` and `src/app/*.rs` both work for a `src/app` target.
+struct PathGlobs {
+    exclude: Option<globset::GlobSet>,
+    include: Option<globset::GlobSet>,
+    repo_root: PathBuf,
+}
+
+impl PathGlobs {
+    fn new(config: &Config, repo_root: &str) -> Result<Self, ContextError> {
+        let exclude_patterns = exclude_patterns_with_ignore_file(config)?;
+        let exclude = if exclude_patterns.is_empty() {
+            None
+        } else {
+            Some(build_globset(&exclude_patterns)?)
+        };
+
+        let include = if config.include_patterns.is_empty() {
+            None
+        } else {
+            Some(build_globset(&config.include_patterns)?)
+        };
+
+        Ok(Self {
+            exclude,
+            include,
+            repo_root: PathBuf::from(repo_root),
+        })
+    }
+
+    /// Whether an exclude pattern matches `path`, given as absolute and relative path
+    fn excludes(&self, path: &Path, rel_path: &str) -> bool {
+        self.exclude
+            .as_ref()
+            .is_some_and(|set| self.is_match(set, path, rel_path))
+    }
+
+    /// Whether `path` passes the include patterns, true when there are none
+    fn includes(&self, path: &Path, rel_path: &str) -> bool {
+        self.include
+            .as_ref()
+            .is_none_or(|set| self.is_match(set, path, rel_path))
+    }
+
+    fn is_match(&self, set: &globset::GlobSet, path: &Path, rel_path: &str) -> bool {
+        set.is_match(rel_path)
+            || path
+                .strip_prefix(&self.repo_root)
+                .is_ok_and(|repo_rel| set.is_match(normalize_path(&repo_rel.to_string_lossy())))
+    }
+}
+
+/// Build the entries for discovered files, keeping the order of `paths`. Files that cannot be
+/// read are skipped with a warning and returned as `(path, reason)` pairs. Runs on all cores
+/// with the `parallel` feature unless `Config::single_threaded` is set.
+fn create_file_entries(
+    paths: Vec<(PathBuf, String)>,
+    config: &Config,
+) -> (Vec<FileEntry>, Vec<(String, String)>) {
+    let total = paths.len();
+    let done = AtomicUsize::new(0);
+    let create = |(path, rel_path): &(PathBuf, String)| {
+        let entry = create_file_entry(path, config);
+        if let Some(progress) = &config.progress {
+            let done = done.fetch_add(1, Ordering::Relaxed) + 1;
+            progress.report(ProgressEvent::Processed { done, total });
+        }
+
+        match entry {
+            Ok(mut file_entry) => {
+                file_entry.path = rel_path.clone();
+                Ok(file_entry)
+            }
+            Err(e) => {
+                eprintln!(
+                    "Warning: Could not process file {}: {}",
+                    path.to_string_lossy(),
+                    e
+                );
+                Err((rel_path.clone(), e.to_string()))
+            }
+        }
+    };
+
+    #[cfg(feature = "parallel")]
+    let results: Vec<_> = if config.single_threaded {
+        paths.iter().map(create).collect()
+    } else {
+        use rayon::prelude::*;
+        // Collecting an indexed parallel iterator keeps the input order
+        paths.par_iter().map(create).collect()
+    };
+    #[cfg(not(feature = "parallel"))]
+    let results: Vec<_> = paths.iter().map(create).collect();
+
+    let mut entries = Vec::new();
+    let mut skipped = Vec::new();
+    for result in results {
+        match result {
+            Ok(entry) => entries.push(entry),
+            Err(failure) => skipped.push(failure),
+        }
+    }
+    (entries, skipped)
+}
+
+/// First and last line (1-based, inclusive) of a `path:start-end` target
+type LineRange = (u64, u64);
+
+/// Split a `path:start-end` target into the path and its 1-based, inclusive line range. A
+/// target naming an existing path is taken as is, so file names containing `:` still work.
+fn split_line_range<'a>(
+    target: &'a str,
+    root_path: &str,
+) -> Result<(&'a str, Option<LineRange>), ContextError> {
+    let unsplit = (target, None);
+    if Path::new(root_path).join(target).exists() {
+        return Ok(unsplit);
+    }
+    let Some((path, range)) = target.rsplit_once(':') else {
+        return Ok(unsplit);
+    };
+    let Some((start, end)) = range.split_once('-') else {
+        return Ok(unsplit);
+    };
+    let (Ok(start), Ok(end)) = (start.parse::<u64>(), end.parse::<u64>()) else {
+        return Ok(unsplit);
+    };
+    if start == 0 || start > end {
+        return Err(ContextError::Other(format!(
+            "Invalid line range in target {}: expected start-end with 1 <= start <= end",
+            target
+        )));
+    }
+    Ok((path, Some((start, end))))
+}
+
+/// Narrow a text file entry to lines `start..=end` of the file, read from disk so a slice of a
+/// file too large to include whole still works. An end past the last line is clamped to it.
+fn select_lines(
+    entry: &mut FileEntry,
+    path: &Path,
+    (start, end): LineRange,
+    config: &Config,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if entry.is_binary {
+        eprintln!(
+            "Warning: Ignoring the line range of binary file {}",
+            path.display()
+        );
+        return Ok(());
+    }
+    let mut reader = BufReader::new(fs::File::open(path)?);
+    let mut selected = Vec::new();
+    let mut line = Vec::new();
+    let mut read = 0;
+    while read < end && reader.read_until(b'\n', &mut line)? > 0 {
+        read += 1;
+        if read >= start {
+            selected.extend_from_slice(&line);
+        }
+        line.clear();
+    }
+    if read < start {
+        return Err(format!(
+            "line range {}-{} starts past the end of the file ({} lines)",
+            start, end, read
+        )
+        .into());
+    }
+    if read < end {
+        eprintln!(
+            "Warning: {} has only {} lines, showing lines {}-{}",
+            path.display(),
+            read,
+            start,
+            read
+        );
+    }
+
+    entry.content_hash = Some(format!("{:x}", Sha256::digest(&selected)));
+    let (content, lossy) = decode_text(selected);
+    entry.lines = read - start + 1;
+    entry.line_ending = LineEnding::detect(&content);
+    entry.tokens = estimate_tokens(&content);
+    entry.content_lossy |= lossy;
+    entry.line_range = Some((start, read));
+    if config.loads_content() {
+        entry.content = Some(content);
+        // The slice is read regardless of the size limit that applies to whole files
+        entry.omitted_reason = None;
+    }
+    Ok(())
+}
+
+/// Content is only loaded for files below this size unless `Config::max_content_bytes` is set
+pub const DEFAULT_MAX_CONTENT_BYTES: u64 = 1_000_000;
+
+fn create_file_entry(
+    path: &Path,
+    config: &Config,
+) -> Result<FileEntry, Box<dyn std::error::Error>> {
     let metadata = fs::metadata(path)?;
     let size = metadata.len();
+    let max_content_bytes = max_content_bytes(config);
+    // Fast path for dry runs: only metadata and line counts are needed
+    let load_content = config.loads_content();
 
     // Determine if file is binary by reading first few bytes
     let is_binary = is_binary_file(path)?;
 
-    // Read content if it's not binary and not too large (e.g., < 1MB)
-    // It'd be fun if the user could configure this limit, too complex for now
-    let content = if !is_binary && size < 1_000_000 {
-        fs::read_to_string(path).ok()
+    // Read content if it's not binary and not too large
+    let too_large = size >= max_content_bytes;
+    let mut content_lossy = false;
+    let (content, content_hash) = if load_content && !is_binary && !too_large {
+        let bytes = fs::read(path)?;
+        let hash = format!("{:x}", Sha256::digest(&bytes));
+        let (content, lossy) = decode_text(bytes);
+        if lossy {
+            // Keep the readable parts rather than dropping the whole file
+            eprintln!(
+                "Warning: {} contains undecodable text, invalid sequences were replaced",
+                path.to_string_lossy()
+            );
+            content_lossy = true;
+        }
+        (Some(content), Some(hash))
     } else {
-        None
+        (None, None)
     };
 
-    let lines = if !is_binary { get_file_lines(path)? } else { 0 };
+    // Binary files are only read when they are going to be embedded
+    let embed_binary = is_binary && config.binary_mode != BinaryMode::Skip;
+    let (raw_bytes, content_hash) = if load_content && embed_binary && !too_large {
+        let bytes = fs::read(path)?;
+        let hash = format!("{:x}", Sha256::digest(&bytes));
+        (Some(bytes), Some(hash))
+    } else {
+        (None, content_hash)
+    };
 
+    let lines = match &content {
+        // Decoded text, so UTF-16 files are counted by character rather than by byte
+        Some(text) => count_lines(text),
+        None if !is_binary => get_file_lines(path)?,
+        None => 0,
+    };
+    let line_ending = content
+        .as_deref()
+        .map(LineEnding::detect)
+        .unwrap_or_default();
+    let tokens = match (&content, is_binary) {
+        (Some(text), _) => estimate_tokens(text),
+        (None, false) => estimate_tokens_from_size(size),
+        (None, true) => 0,
+    };
     Ok(FileEntry {
         path: path.to_string_lossy().to_string(),
         content,
         size,
         lines,
+        line_ending,
+        tokens,
         is_binary,
+        last_commit_time: None,
+        last_commit: None,
+        modified: metadata.modified().ok(),
+        source_path: path.to_path_buf(),
+        line_range: None,
+        changed: None,
+        content_hash,
+        content_lossy,
+        raw_bytes,
+        omitted_reason: ((!is_binary || embed_binary) && too_large).then(|| {
+            format!(
+                "file size of {} bytes exceeds the {} byte limit",
+                size, max_content_bytes
+            )
+        }),
     })
 }
 
+fn max_content_bytes(config: &Config) -> u64 {
+    config
+        .max_content_bytes
+        .unwrap_or(DEFAULT_MAX_CONTENT_BYTES)
+}
+
+/// Byte order of a UTF-16 text file
+#[derive(Debug, Clone, Copy)]
+enum Utf16Order {
+    Little,
+    Big,
+}
+
+/// Byte order announced by a UTF-16 byte order mark at the start of `bytes`.
+/// `FF FE 00 00` is the UTF-32 LE mark and is deliberately not treated as UTF-16.
+fn utf16_bom(bytes: &[u8]) -> Option<Utf16Order> {
+    match bytes {
+        [0xFF, 0xFE, 0x00, 0x00, ..] => None,
+        [0xFF, 0xFE, ..] => Some(Utf16Order::Little),
+        [0xFE, 0xFF, ..] => Some(Utf16Order::Big),
+        _ => None,
+    }
+}
+
+/// Decode file bytes as UTF-16 when they start with a UTF-16 byte order mark, UTF-8 otherwise.
+/// Returns the text and whether undecodable sequences were replaced.
+pub(crate) fn decode_text(bytes: Vec<u8>) -> (String, bool) {
+    let Some(order) = utf16_bom(&bytes) else {
+        return match String::from_utf8(bytes) {
+            Ok(text) => (text, false),
+            Err(e) => (String::from_utf8_lossy(e.as_bytes()).into_owned(), true),
+        };
+    };
+
+    let body = &bytes[2..];
+    let (pairs, _) = body.as_chunks::<2>();
+    let units = pairs.iter().map(|pair| match order {
+        Utf16Order::Little => u16::from_le_bytes(*pair),
+        Utf16Order::Big => u16::from_be_bytes(*pair),
+    });
+    // A trailing odd byte cannot be decoded
+    let odd_length = !body.len().is_multiple_of(2);
+    let mut lossy = odd_length;
+    let mut text: String = char::decode_utf16(units)
+        .map(|unit| {
+            unit.unwrap_or_else(|_| {
+                lossy = true;
+                char::REPLACEMENT_CHARACTER
+            })
+        })
+        .collect();
+    if odd_length {
+        text.push(char::REPLACEMENT_CHARACTER);
+    }
+    (text, lossy)
+}
+
 /// Simple heuristic to determine if a file is binary
 /// Source: https://post.bytes.com/forum/topic/python/18010-determine-file-type-binary-or-text
 fn is_binary_file(path: &Path) -> Result<bool, Box<dyn std::error::Error>> {
     // Read first 512 bytes to check for binary content
     let mut buffer = [0; 512];
 
-    match fs::File::open(path) {
-        Ok(mut file) => {
-            use std::io::Read;
-            let bytes_read = file.read(&mut buffer)?;
+    // A file that cannot be opened is reported as skipped rather than listed as binary
+    use std::io::Read;
+    let bytes_read = fs::File::open(path)?.read(&mut buffer)?;
 
-            // Check for null bytes (common indicator of binary files)
-            let is_binary = buffer[..bytes_read].contains(&0);
-            Ok(is_binary)
-        }
-        Err(_) => Ok(true), // Assume binary if we can't read
+    // UTF-16 text is full of null bytes, but announces itself with a byte order mark
+    if utf16_bom(&buffer[..bytes_read]).is_some() {
+        return Ok(false);
     }
+
+    // Check for null bytes (common indicator of binary files)
+    let is_binary = buffer[..bytes_read].contains(&0);
+    Ok(is_binary)
 }
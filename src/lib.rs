@@ -19,16 +19,22 @@
 pub use types::*;
 
 pub mod cli;
+pub mod config_file;
 pub mod context;
+pub mod error;
 pub mod files;
 pub mod git;
+pub mod manifest;
 pub mod output;
+pub mod skeleton;
 pub mod tree;
 /// Internal modules
 pub mod types;
+pub mod utils;
 
 // Re-export key functionality
 pub use cli::Cli;
 pub use context::ContextManager;
+pub use error::ContextError;
 pub use output::{OutputContext, OutputDestination, OutputFormat};
 pub use tree::TreeContext;
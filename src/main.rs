@@ -15,11 +15,18 @@
 //
 
 use clap::Parser;
+use rusty_repo_context_manager::cli::EXCLUDE_TESTS_PRESET;
+use rusty_repo_context_manager::config_file::{ConfigFile, DEFAULT_CONFIG_FILE};
+use rusty_repo_context_manager::files::read_target_paths;
+use rusty_repo_context_manager::output::dry_run_report;
 use rusty_repo_context_manager::{
-    Cli, Config, ContextManager, OutputContext, OutputDestination, OutputFormat,
+    BinaryMode, Cli, Config, ContextManager, OutputContext, OutputDestination, OutputFormat,
 };
+#[cfg(feature = "progress")]
+use rusty_repo_context_manager::{ProgressEvent, ProgressHook};
+use std::path::Path;
 
-/// Create a Config from parsed CLI arguments
+/// Create a Config from parsed CLI arguments, on top of the defaults from the config file
 fn create_config_from_cli(cli: Cli) -> Result<Config, Box<dyn std::error::Error>> {
     let current_dir =
         std::env::current_dir().map_err(|e| format!("Failed to get current directory: {}", e))?;
@@ -28,41 +35,254 @@ fn create_config_from_cli(cli: Cli) -> Result<Config, Box<dyn std::error::Error>
         .ok_or("Failed to convert current directory to string")?
         .to_string();
 
+    let file = match &cli.config {
+        Some(path) => ConfigFile::load(Path::new(path), true)?,
+        None => ConfigFile::load(&current_dir.join(DEFAULT_CONFIG_FILE), false)?,
+    };
+    // Only an explicit --recursive or --no-recursive overrides the file
+    let is_recursive = if cli.recursive || cli.no_recursive {
+        cli.is_recursive()
+    } else {
+        file.recursive.unwrap_or(true)
+    };
+    let recent = cli.recent.or(file.recent_window()?);
+
+    let mut target_paths = cli.target_paths;
+    if cli.stdin || target_paths.iter().any(|path| path == "-") {
+        target_paths.retain(|path| path != "-");
+        let piped = read_target_paths(std::io::stdin().lock())
+            .map_err(|e| format!("Failed to read target paths from stdin: {}", e))?;
+        // An empty selection would otherwise fall back to packaging the whole repository
+        if piped.is_empty() && target_paths.is_empty() && cli.files_from.is_none() {
+            return Err("No target paths were read from stdin".into());
+        }
+        target_paths.extend(piped);
+    }
+
     Ok(Config {
         root_path,
-        target_paths: cli.target_paths,
-        output_file: cli.output,
-        include_patterns: cli.include.unwrap_or_default(),
-        exclude_patterns: cli.exclude.unwrap_or_default(),
-        is_recursive: cli.recursive,
-        recent_only: cli.recent,
-        show_line_numbers: cli.line_numbers,
+        is_recursive,
+        target_paths,
+        use_git: !cli.no_git,
+        allow_outside_root: cli.allow_outside_root,
+        output_file: cli.output.or(file.output),
+        clipboard: cli.clipboard,
+        tee: cli.tee,
+        include_patterns: cli.include.or(file.include_patterns).unwrap_or_default(),
+        exclude_patterns: exclude_patterns(
+            cli.exclude.or(file.exclude_patterns),
+            cli.exclude_tests,
+        ),
+        recent_only: recent.is_some(),
+        recent_window: recent,
+        files_from: cli.files_from,
+        ignore_missing: cli.ignore_missing,
+        max_output_bytes: cli.max_output_size,
+        show_line_numbers: cli.line_numbers || file.line_numbers.unwrap_or(false),
+        show_file_age: cli.age,
+        git_annotate: cli.git_annotate,
+        expand_tabs: cli.expand_tabs,
+        tabs_leading_only: cli.tabs_leading_only,
+        max_line_width: cli.max_line_width,
+        git_note_ref: cli.output_to_git_note,
+        focus_changes: cli.focus_changes,
+        dedupe_whitespace_only_diff: cli.dedupe_whitespace_only_diff,
+        dedupe: cli.dedupe,
+        path_style: cli.path_style,
+        only_hashes: cli.only_hashes,
+        show_size_rank: cli.rank,
+        split_by_ext: cli.split_by_ext,
+        whitespace_stats: cli.whitespace_stats,
+        trim_outliers: cli.trim_outliers,
+        git_graph: cli.git_graph,
+        git_log: cli.git_log,
+        git_status: cli.git_status,
+        tree_json: cli.tree_json,
+        max_total_render_lines: cli.max_total_render_lines,
+        max_tokens: cli.max_tokens,
+        redact_path: cli.redact_path,
+        redact: cli.redact,
+        deps: cli.deps,
+        staged: cli.staged,
+        range: cli.range,
+        skip_data: cli.skip_data,
+        report_output_size: cli.report_output_size,
+        exclude_contains: cli.exclude_contains.unwrap_or_default(),
+        include_exts: cli.ext,
+        exclude_exts: cli.no_ext,
+        overview: cli.overview,
+        line_number_style: cli.line_number_style,
+        binary_mode: cli.binary,
+        author: cli.author,
+        changed_since: cli.changed_since,
+        content_addressed: cli.content_addressed,
+        gzip: cli.gzip,
+        todos: cli.todos || cli.todo_marker.is_some(),
+        todo_markers: cli.todo_marker.unwrap_or_default(),
+        dir_summaries: cli.dir_summaries,
+        group_by: cli.group_by,
+        sort: cli.sort,
+        sort_descending: cli.reverse,
+        primary_lang_only: cli.primary_lang_only,
+        skeleton: cli.skeleton,
+        heading_offset: cli.heading_offset,
+        include_submodules: cli.include_submodules,
+        follow_symlinks: cli.follow_symlinks,
+        max_content_bytes: cli.max_file_size,
+        skip_larger_than: cli.skip_larger_than,
+        max_files: cli.max_files,
+        tree_max_children: cli.tree_max_children,
+        lang_breakdown_limit: cli.lang_breakdown_limit,
+        max_depth: cli.max_depth,
+        ascii_tree: cli.ascii_tree,
+        stable: cli.stable,
+        reading_time: cli.reading_time,
+        complexity: cli.complexity,
+        exclude_empty: cli.exclude_empty,
+        min_lines: cli.min_lines,
+        include_hidden: cli.hidden,
+        single_threaded: cli.single_threaded,
+        front_matter: cli.front_matter,
+        dry_run: cli.dry_run,
+        metadata_only: cli.metadata_only,
+        progress: None,
     })
 }
 
+/// Explicit exclude patterns followed by any enabled presets
+fn exclude_patterns(explicit: Option<Vec<String>>, exclude_tests: bool) -> Vec<String> {
+    let mut patterns = explicit.unwrap_or_default();
+    if exclude_tests {
+        patterns.extend(EXCLUDE_TESTS_PRESET.iter().map(|p| p.to_string()));
+    }
+    patterns
+}
+
 /// Determine output destination from config
 fn determine_output_destination(config: &Config) -> OutputDestination {
+    if config.clipboard {
+        return OutputDestination::Clipboard;
+    }
     match &config.output_file {
+        Some(path) if config.tee => OutputDestination::Both(path.clone()),
         Some(path) => OutputDestination::File(path.clone()),
-        None => OutputDestination::Stdout,
+        None => {
+            if config.tee {
+                eprintln!("Warning: --tee only applies when writing to a file (-o)");
+            }
+            OutputDestination::Stdout
+        }
     }
 }
 
+/// Options given that only the markdown writer applies, e.g. to warn when writing HTML
+fn markdown_only_options(config: &Config) -> Vec<&'static str> {
+    [
+        (config.max_tokens.is_some(), "--max-tokens"),
+        (config.dedupe, "--dedupe"),
+        (config.group_by.is_some(), "--group-by"),
+        (
+            config.max_total_render_lines.is_some(),
+            "--max-total-render-lines",
+        ),
+        (config.focus_changes.is_some(), "--focus-changes"),
+        (config.range.is_some(), "--range"),
+        (config.binary_mode != BinaryMode::Skip, "--binary"),
+        (config.front_matter, "--front-matter"),
+        (config.show_size_rank, "--rank"),
+        (config.show_file_age, "--age"),
+        (config.git_annotate, "--git-annotate"),
+        (config.dir_summaries, "--dir-summaries"),
+        (config.heading_offset > 0, "--heading-offset"),
+        (config.overview, "--overview"),
+        (config.complexity, "--complexity"),
+        (config.todos, "--todos"),
+        (config.deps, "--deps"),
+        (config.whitespace_stats, "--whitespace-stats"),
+        (config.reading_time, "--reading-time"),
+        (config.git_log > 0, "--git-log"),
+        (config.git_graph.is_some(), "--git-graph"),
+        (config.git_status, "--git-status"),
+    ]
+    .into_iter()
+    .filter_map(|(given, flag)| given.then_some(flag))
+    .collect()
+}
+
+/// Spinner on stderr that follows discovery, None when stderr is not a terminal
+#[cfg(feature = "progress")]
+fn progress_bar(enabled: bool) -> Option<indicatif::ProgressBar> {
+    use std::io::IsTerminal;
+    (enabled && std::io::stderr().is_terminal()).then(indicatif::ProgressBar::new_spinner)
+}
+
+/// Forward discovery progress to the spinner
+#[cfg(feature = "progress")]
+fn progress_hook(bar: &indicatif::ProgressBar) -> ProgressHook {
+    let bar = bar.clone();
+    ProgressHook::new(move |event| match event {
+        ProgressEvent::Discovered(found) => {
+            bar.set_message(format!("Discovering files: {} found", found))
+        }
+        ProgressEvent::Processed { done, total } => {
+            bar.set_message(format!("Reading files: {}/{}", done, total))
+        }
+    })
+}
+
 #[allow(deprecated)]
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     let cli = Cli::parse();
+    let print_config = cli.print_config;
+    let format = cli.format.clone();
+    #[cfg(feature = "progress")]
+    let progress_bar = progress_bar(cli.progress);
+    #[cfg(not(feature = "progress"))]
+    if cli.progress {
+        eprintln!("Warning: --progress requires building with the `progress` feature");
+    }
     let config = create_config_from_cli(cli)?;
 
+    if matches!(format, OutputFormat::Html) {
+        let ignored = markdown_only_options(&config);
+        if !ignored.is_empty() {
+            eprintln!(
+                "Warning: --format html ignores these markdown-only options: {}",
+                ignored.join(", ")
+            );
+        }
+    }
+
+    if print_config {
+        println!("{}", serde_json::to_string_pretty(&config)?);
+        return Ok(());
+    }
+
+    #[cfg(feature = "progress")]
+    let config = Config {
+        progress: progress_bar.as_ref().map(progress_hook),
+        ..config
+    };
     let mut manager = ContextManager::new(config.clone());
     manager.build_context().unwrap_or_else(|e| {
         eprintln!("Error building context: {}", e);
         std::process::exit(1);
     });
+    #[cfg(feature = "progress")]
+    if let Some(bar) = progress_bar {
+        bar.finish_and_clear();
+    }
+
+    if config.dry_run {
+        let context = manager.context().ok_or("Context not built")?;
+        print!("{}", dry_run_report(&context.file_ctx));
+        return Ok(());
+    }
 
     let output_dest = determine_output_destination(&config);
 
     OutputContext::new(manager)
-        .format(OutputFormat::Markdown)
+        .format(format)
         .destination(output_dest)
         .generate()?;
 
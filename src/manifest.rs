@@ -0,0 +1,73 @@
+//===----------------------------------------------------------------------===//
+//
+// Copyright (c) 2025 David Rivera
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+//
+// SPDX-License-Identifier: MIT
+//
+//===----------------------------------------------------------------------===//
+//
+// Reads the dependency tables of a Cargo manifest for the `--deps` summary.
+//===----------------------------------------------------------------------===//
+//
+
+use crate::types::Dependency;
+use std::fs;
+use std::path::Path;
+use toml::{Table, Value};
+
+/// Dependencies declared in `<root>/Cargo.toml`, runtime ones first, each table sorted by name.
+/// Returns an empty list when there is no manifest.
+pub fn cargo_dependencies(root: &Path) -> Result<Vec<Dependency>, Box<dyn std::error::Error>> {
+    let manifest_path = root.join("Cargo.toml");
+    if !manifest_path.is_file() {
+        return Ok(Vec::new());
+    }
+
+    let manifest: Table = fs::read_to_string(&manifest_path)?
+        .parse()
+        .map_err(|e| format!("Failed to parse {}: {}", manifest_path.display(), e))?;
+
+    let mut dependencies = Vec::new();
+    for (table, dev) in [("dependencies", false), ("dev-dependencies", true)] {
+        let Some(Value::Table(entries)) = manifest.get(table) else {
+            continue;
+        };
+        // toml tables are ordered by key, so the output is already sorted by crate name
+        for (name, spec) in entries {
+            dependencies.push(Dependency {
+                name: name.clone(),
+                requirement: describe_requirement(spec),
+                dev,
+            });
+        }
+    }
+
+    Ok(dependencies)
+}
+
+/// Human readable form of a dependency specification: its version requirement when there is
+/// one, otherwise where the crate comes from
+fn describe_requirement(spec: &Value) -> String {
+    let table = match spec {
+        Value::String(version) => return version.clone(),
+        Value::Table(table) => table,
+        other => return other.to_string(),
+    };
+
+    if table.get("workspace").and_then(Value::as_bool) == Some(true) {
+        return "inherited from workspace".to_string();
+    }
+    if let Some(version) = table.get("version").and_then(Value::as_str) {
+        return version.to_string();
+    }
+    if let Some(path) = table.get("path").and_then(Value::as_str) {
+        return format!("path {}", path);
+    }
+    if let Some(git) = table.get("git").and_then(Value::as_str) {
+        return format!("git {}", git);
+    }
+    "unspecified".to_string()
+}
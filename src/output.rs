@@ -14,16 +14,42 @@
 //===----------------------------------------------------------------------===//
 //
 
-use std::io::Write;
+use std::borrow::Cow;
+use std::collections::hash_map::Entry;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::io::{BufWriter, Write};
+use std::path::Path;
 
-use crate::{ContextManager, FileContext, FileEntry, RepositoryContext};
+use chrono::{DateTime, Utc};
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::Serialize;
+
+use crate::error::ContextError;
+use crate::git;
+use crate::skeleton::skeletonize;
+use crate::utils::{
+    count_lines, extension_key, fence_language, file_role, format_timestamp, language_name,
+};
+use crate::{
+    BinaryMode, Config, ContextManager, Dependency, FileContext, FileEntry, FileRole, GitInfo,
+    GroupBy, LineEnding, PathStyle, RangeDiff, RepositoryContext,
+};
 
 /// Simple output format options
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, clap::ValueEnum)]
 pub enum OutputFormat {
+    #[value(skip)]
     Plain,
+    #[value(skip)]
     Json,
+    /// Markdown document
+    #[default]
     Markdown,
+    /// Self-contained HTML page
+    Html,
+    /// JSON Lines: a metadata object, then one file entry per line
+    Jsonl,
 }
 
 impl OutputFormat {
@@ -32,6 +58,8 @@ impl OutputFormat {
             OutputFormat::Plain => "txt",
             OutputFormat::Json => "json",
             OutputFormat::Markdown => "md",
+            OutputFormat::Html => "html",
+            OutputFormat::Jsonl => "jsonl",
         }
     }
 }
@@ -41,16 +69,21 @@ impl OutputFormat {
 pub enum OutputDestination {
     Stdout,
     File(String),
+    /// Both stdout and a file, written in one pass
+    Both(String),
+    /// System clipboard, only available with the `clipboard` feature
+    Clipboard,
 }
 
+/// Number of fingerprint hex digits used in content-addressed file names
+const FINGERPRINT_PREFIX_LEN: usize = 12;
+
 /// Simple builder for outputting repository context
 pub struct OutputContext {
     // should be moved to a ContextManager instance ideally?
     context_manager: ContextManager,
     format: OutputFormat,
     destination: OutputDestination,
-    /// Output buffer: Content of the repo indexed by file path
-    buffer: String,
 }
 
 impl OutputContext {
@@ -63,7 +96,6 @@ impl OutputContext {
             context_manager,
             format: OutputFormat::Markdown,
             destination: OutputDestination::Stdout,
-            buffer: String::new(),
         }
     }
 
@@ -79,267 +111,2186 @@ impl OutputContext {
         self
     }
 
-    /// Generate and output the repository context
-    pub fn generate(mut self) -> Result<(), Box<dyn std::error::Error>> {
+    /// Generate and output the repository context.
+    /// Stdout and file destinations are written section by section as the output is
+    /// formatted; the clipboard and git notes need the whole text, so those are buffered.
+    pub fn generate(self) -> Result<(), ContextError> {
         let context = self
             .context_manager
             .context
             .as_ref()
             .ok_or("Context not built")?;
 
-        match &self.format {
-            OutputFormat::Plain => todo!("Format as Plain Text Not yet implemented"), // I may never implement this
-            OutputFormat::Json => todo!("Format as JSON Not yet implemented"),
-            OutputFormat::Markdown => {
-                let markdown_output = self.format_markdown(context);
-                self.buffer.push_str(&markdown_output);
+        if let Some(out_dir) = &context.file_ctx.config.split_by_ext {
+            if self.context_manager.config.git_note_ref.is_some() {
+                eprintln!("Warning: --output-to-git-note is ignored with --split-by-ext");
+            }
+            self.write_split_by_ext(context, out_dir)?;
+            if let Some(tree_json) = &self.context_manager.config.tree_json {
+                write_tree_json(context, tree_json)?;
+            }
+            return Ok(());
+        }
+
+        let to_file = matches!(
+            self.destination,
+            OutputDestination::File(_) | OutputDestination::Both(_)
+        );
+        if context.file_ctx.config.content_addressed && !to_file {
+            eprintln!("Warning: --content-addressed only applies when writing to a file (-o)");
+        }
+        let gzip = context.file_ctx.config.gzip;
+        if gzip && !to_file {
+            eprintln!("Warning: --gzip only applies when writing to a file (-o)");
+        }
+
+        match (&self.destination, &self.context_manager.config.git_note_ref) {
+            (OutputDestination::Stdout, None) => {
+                self.stream_to(context, std::io::stdout().lock())?;
+            }
+            (OutputDestination::File(base) | OutputDestination::Both(base), None) => {
+                let path = self.output_file_path(context, base);
+                let mut file = match OutputFile::create(&path, gzip) {
+                    Ok(file) => file,
+                    Err(source) => return Err(ContextError::OutputCreation { path, source }),
+                };
+                if matches!(self.destination, OutputDestination::Both(_)) {
+                    let tee = TeeWriter {
+                        first: std::io::stdout().lock(),
+                        second: &mut file,
+                    };
+                    self.stream_to(context, tee)?;
+                } else {
+                    self.stream_to(context, &mut file)?;
+                }
+                file.finish()
+                    .map_err(|source| ContextError::OutputCreation { path, source })?;
+            }
+            (_, notes_ref) => {
+                let mut buffer = Vec::new();
+                self.write_output(context, &mut buffer)?;
+                let buffer = String::from_utf8(buffer).expect("markdown output is valid UTF-8");
+
+                // Reported on stderr so the artifact itself is left untouched
+                if self.context_manager.config.report_output_size {
+                    eprintln!("{}", output_size_report(&buffer));
+                }
+
+                if matches!(
+                    self.destination,
+                    OutputDestination::Stdout | OutputDestination::Both(_)
+                ) {
+                    print!("{}", buffer);
+                }
+                match &self.destination {
+                    OutputDestination::Stdout => {}
+                    OutputDestination::File(path) | OutputDestination::Both(path) => {
+                        let path = self.output_file_path(context, path);
+                        write_output_file(&path, buffer.as_bytes(), gzip)
+                            .map_err(|source| ContextError::OutputCreation { path, source })?;
+                    }
+                    OutputDestination::Clipboard => copy_to_clipboard(&buffer)?,
+                }
+
+                if let Some(notes_ref) = notes_ref {
+                    self.write_git_note(notes_ref, &buffer)?;
+                }
             }
         }
 
-        match &self.destination {
-            OutputDestination::Stdout => {
-                print!("{}", self.buffer);
+        if let Some(tree_json) = &self.context_manager.config.tree_json {
+            write_tree_json(context, tree_json)?;
+        }
+
+        Ok(())
+    }
+
+    /// Write one output file per file extension into `out_dir`.
+    /// Each file repeats the shared header and carries a summary of its own group.
+    fn write_split_by_ext(
+        &self,
+        context: &RepositoryContext,
+        out_dir: &str,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        std::fs::create_dir_all(out_dir)
+            .map_err(|e| format!("Failed to create output directory {}: {}", out_dir, e))?;
+
+        let mut groups: BTreeMap<String, Vec<FileEntry>> = BTreeMap::new();
+        for file in &context.file_ctx.file_entries {
+            groups
+                .entry(split_bucket(&file.path))
+                .or_default()
+                .push(file.clone());
+        }
+
+        for (ext, entries) in groups {
+            let mut group_context = context.clone();
+            group_context.file_ctx.file_entries = entries;
+
+            if !matches!(self.format, OutputFormat::Markdown | OutputFormat::Html) {
+                return Err(format!("--split-by-ext does not support {:?}", self.format).into());
             }
-            OutputDestination::File(path) => {
-                let mut file =
-                    std::fs::File::create(format!("{}.{}", path, self.format.to_extension()))?;
-                file.write_all(self.buffer.as_bytes())?;
+
+            // Each file goes through the same writer stack as -o, so the size limit,
+            // gzip and size report apply per file
+            let path = Path::new(out_dir).join(format!("{}.{}", ext, self.format.to_extension()));
+            let mut path = path.to_string_lossy().into_owned();
+            if group_context.file_ctx.config.gzip {
+                path.push_str(".gz");
             }
+            let mut file = match OutputFile::create(&path, group_context.file_ctx.config.gzip) {
+                Ok(file) => file,
+                Err(source) => return Err(ContextError::OutputCreation { path, source }.into()),
+            };
+            self.stream_to(&group_context, &mut file)?;
+            file.finish()
+                .map_err(|source| ContextError::OutputCreation { path, source })?;
         }
 
         Ok(())
     }
 
+    /// Store the generated output as a git note on HEAD
+    fn write_git_note(
+        &self,
+        notes_ref: &str,
+        output: &str,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let root_path = &self.context_manager.config.root_path;
+        let repo = git2::Repository::discover(root_path).map_err(|e| {
+            format!(
+                "Cannot write git note: {} is not inside a git repository ({})",
+                root_path, e
+            )
+        })?;
+
+        _ = git::write_head_note(&repo, notes_ref, output)
+            .map_err(|e| format!("Failed to write git note to {}: {}", notes_ref, e))?;
+        Ok(())
+    }
+
     /// Format as markdown
     fn format_markdown(&self, context: &RepositoryContext) -> String {
+        let mut buffer = Vec::new();
+        self.write_markdown(context, &mut buffer)
+            .expect("writing to an in-memory buffer cannot fail");
+        String::from_utf8(buffer).expect("markdown output is valid UTF-8")
+    }
+
+    /// Write the markdown output section by section, so at most one file block is held in
+    /// memory at a time when `out` is a file
+    fn write_markdown<W: Write>(
+        &self,
+        context: &RepositoryContext,
+        out: &mut W,
+    ) -> std::io::Result<()> {
         let mut output = String::new();
 
         // dump header
-        output.push_str("# Repository Context \n\n");
+        let offset = context.file_ctx.config.heading_offset;
+        output.push_str(&heading(1, offset, "Repository Context "));
+
+        if context.file_ctx.config.overview {
+            output.push_str(&dump_overview(context));
+        }
 
         //dump repo metadata
         output.push_str(&dump_repo_metadata_md(context));
 
         // dump tree structure
-        output.push_str(&dump_tree_structure(&self.context_manager));
+        output.push_str(&dump_tree_structure(&context.tree_repr, offset));
 
         // dump each file entry
-        for file in &context.file_ctx.file_entries {
-            output.push_str(&format!(
-                "  {}\n\n",
-                dump_file_entry(file, context.file_ctx.config.show_line_numbers)
+        let config = &context.file_ctx.config;
+        let size_ranks = if config.show_size_rank {
+            compute_size_ranks(&context.file_ctx.file_entries)
+        } else {
+            HashMap::new()
+        };
+        let over_budget = match config.max_tokens {
+            Some(budget) => files_over_token_budget(&context.file_ctx.file_entries, budget),
+            None => HashSet::new(),
+        };
+        // Content hash -> path of the first file rendered with that content, for --dedupe
+        let mut first_by_hash: HashMap<&str, &str> = HashMap::new();
+        let mut rendered_lines = 0;
+        let mut render_cap_reached = false;
+        for (group, files) in group_entries(&context.file_ctx.file_entries, config) {
+            if config.group_by.is_some() {
+                output.push_str(&heading(3, offset, &group));
+            }
+
+            for file in files.iter().copied() {
+                drain(&mut output, out)?;
+
+                if over_budget.contains(file.path.as_str()) {
+                    continue;
+                }
+
+                // In focus mode unchanged files collapse into a single line
+                if file.changed == Some(false) {
+                    output.push_str(&format!(
+                        "- {} ({} lines, unchanged)\n\n",
+                        display_path(&file.path, config, &context.root_path),
+                        file.lines
+                    ));
+                    continue;
+                }
+
+                let duplicate_of = match &file.content_hash {
+                    Some(hash) if config.dedupe => match first_by_hash.entry(hash.as_str()) {
+                        Entry::Occupied(first) => Some(*first.get()),
+                        Entry::Vacant(slot) => {
+                            _ = slot.insert(file.path.as_str());
+                            None
+                        }
+                    },
+                    _ => None,
+                };
+                if let Some(first) = duplicate_of {
+                    output.push_str(&format!(
+                        "  {}\n\n",
+                        dump_duplicate_entry(file, first, config, &context.root_path)
+                    ));
+                    continue;
+                }
+
+                // Once the global line budget is spent, keep listing files without their content
+                let file = match (config.max_total_render_lines, &file.content) {
+                    (Some(cap), Some(content)) => {
+                        let lines = count_lines(content) as usize;
+                        if !render_cap_reached && rendered_lines + lines <= cap {
+                            rendered_lines += lines;
+                            Cow::Borrowed(file)
+                        } else {
+                            render_cap_reached = true;
+                            Cow::Owned(FileEntry {
+                                content: None,
+                                omitted_reason: Some(format!(
+                                    "total render limit of {} lines reached",
+                                    cap
+                                )),
+                                ..file.clone()
+                            })
+                        }
+                    }
+                    _ => Cow::Borrowed(file),
+                };
+
+                output.push_str(&format!(
+                    "  {}\n\n",
+                    dump_file_entry(
+                        &file,
+                        config,
+                        &context.root_path,
+                        size_ranks.get(file.path.as_str())
+                    )
+                ));
+            }
+
+            if config.dir_summaries && config.group_by.is_none() {
+                output.push_str(&dump_dir_summary(&group, &files));
+            }
+        }
+
+        if let Some(budget) = config.max_tokens {
+            output.push_str(&dump_token_budget_note(
+                &context.file_ctx.file_entries,
+                &over_budget,
+                budget,
             ));
         }
 
         output.push_str(&dump_separator_md());
-        output.push_str("## Summary\n\n");
+        output.push_str(&heading(2, offset, "Summary"));
 
         // dump summary
         output.push_str(&dump_file_context_summary(&context.file_ctx));
 
-        output
+        if config.deps {
+            output.push_str(&dump_dependencies(&context.dependencies, offset));
+        }
+
+        drain(&mut output, out)
     }
 
-    /// Format as JSON
-    #[allow(unused_variables, dead_code)]
-    fn format_json(&self, context: &RepositoryContext) -> String {
-        todo!("Format as JSON NYI")
+    /// Render the context to a string without writing it anywhere
+    pub fn render(&self) -> Result<String, Box<dyn std::error::Error>> {
+        let context = self
+            .context_manager
+            .context
+            .as_ref()
+            .ok_or("Context not built")?;
+
+        match &self.format {
+            OutputFormat::Markdown => Ok(self.format_markdown(context)),
+            OutputFormat::Html => Ok(self.format_html(context)),
+            OutputFormat::Jsonl => Ok(self.format_jsonl(context)),
+            other => Err(format!("Rendering {:?} is not implemented", other).into()),
+        }
     }
-}
 
-fn dump_file_entry(file: &FileEntry, show_line_numbers: bool) -> String {
-    let mut output = String::new();
-    // Include file size in bytes in the file header when available
-    output.push_str(&format!(
-        "## FILE: {}{}\n\n",
-        file.path,
-        if file.size > 0 {
-            format!(" ({} bytes)", file.size)
+    /// Final output file name for a file destination
+    fn output_file_path(&self, context: &RepositoryContext, base: &str) -> String {
+        let base = if context.file_ctx.config.content_addressed {
+            let fingerprint = context.file_ctx.fingerprint();
+            format!("{}-{}", base, &fingerprint[..FINGERPRINT_PREFIX_LEN])
         } else {
-            String::new()
+            base.to_string()
+        };
+        let path = format!("{}.{}", base, self.format.to_extension());
+        if context.file_ctx.config.gzip {
+            format!("{}.gz", path)
+        } else {
+            path
         }
-    ));
+    }
 
-    if let Some(content) = &file.content {
-        let language = get_file_extension(&file.path);
-        output.push_str(&format!("```{}\n", language));
+    /// Write the output in the selected format to `out`
+    /// With `Config::max_output_bytes`, rendering stops once the limit would be exceeded and
+    /// a truncation notice is appended, which itself fits within the limit.
+    fn write_output<W: Write>(
+        &self,
+        context: &RepositoryContext,
+        out: &mut W,
+    ) -> std::io::Result<()> {
+        let Some(limit) = context.file_ctx.config.max_output_bytes else {
+            return self.write_format(context, out);
+        };
 
-        if show_line_numbers {
-            for (i, line) in content.lines().enumerate() {
-                output.push_str(&format!("{}: {}\n", i + 1, line));
-            }
-            // If the original content did not end with a newline, preserve that final line ending
-            if !content.ends_with('\n') {
-                output.push('\n');
-            }
-        } else {
-            output.push_str(content);
-            if !content.ends_with('\n') {
-                output.push('\n');
+        let notice = format!(
+            "\n... output truncated at the {} limit of --max-output-size ...\n",
+            format_bytes(limit)
+        );
+        let mut limited = LimitedWriter::new(&mut *out, limit.saturating_sub(notice.len() as u64));
+        match self.write_format(context, &mut limited) {
+            Err(_) if limited.truncated => {
+                eprintln!(
+                    "Warning: output exceeded --max-output-size of {} and was truncated",
+                    format_bytes(limit)
+                );
+                out.write_all(notice.as_bytes())
             }
+            result => result,
         }
+    }
 
-        output.push_str("```\n");
-    } else if file.is_binary {
-        output.push_str("*Binary file - content not displayed*\n");
-    } else {
-        output.push_str("*Content not available*\n");
+    /// Write the output in the selected format to `out`, without any size limit
+    fn write_format<W: Write>(
+        &self,
+        context: &RepositoryContext,
+        out: &mut W,
+    ) -> std::io::Result<()> {
+        match &self.format {
+            OutputFormat::Plain => todo!("Format as Plain Text Not yet implemented"), // I may never implement this
+            OutputFormat::Json => todo!("Format as JSON Not yet implemented"),
+            OutputFormat::Markdown => self.write_markdown(context, out),
+            OutputFormat::Html => self.write_html(context, out),
+            OutputFormat::Jsonl => self.write_jsonl(context, out),
+        }
     }
 
-    output
-}
+    /// Write the output straight to `out` as it is formatted, without holding it all in memory
+    fn stream_to<W: Write>(
+        &self,
+        context: &RepositoryContext,
+        out: W,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let mut writer = CountingWriter::new(BufWriter::new(out));
+        self.write_output(context, &mut writer)?;
+        writer.flush()?;
 
-fn dump_repo_metadata_md(repo_context: &RepositoryContext) -> String {
-    let mut output = String::new();
-    // TODO(0.1): All matadata would be dumped here
+        if context.file_ctx.config.report_output_size {
+            eprintln!("{}", size_report(writer.bytes, writer.lines()));
+        }
+        Ok(())
+    }
 
-    output.push_str("## Metadata\n\n");
-    output.push_str("### File System Location\n\n");
-    output.push_str(&format!("{}\n\n", repo_context.root_path));
-    output.push_str("### Git Information\n\n");
-    output.push_str(&dump_git_info_md(&repo_context.git_info));
-    output.push_str(&dump_separator_md());
-    output
-}
+    /// Format as a standalone HTML page
+    fn format_html(&self, context: &RepositoryContext) -> String {
+        let mut buffer = Vec::new();
+        self.write_html(context, &mut buffer)
+            .expect("writing to an in-memory buffer cannot fail");
+        String::from_utf8(buffer).expect("HTML output is valid UTF-8")
+    }
 
-fn dump_git_info_md(git_info: &crate::types::GitInfo) -> String {
-    let mut output = String::new();
+    /// Write a self-contained HTML page: metadata, the tree and one section per file. Code
+    /// blocks carry a `language-*` class so a highlighter can be attached, but no script or
+    /// external stylesheet is needed to read the page.
+    fn write_html<W: Write>(
+        &self,
+        context: &RepositoryContext,
+        out: &mut W,
+    ) -> std::io::Result<()> {
+        let config = &context.file_ctx.config;
+        let mut output = String::from(HTML_HEAD);
 
-    if git_info.is_repo {
-        output.push_str(&format!(
-            "- **Commit Hash**: {}\n",
-            git_info.commit_hash.as_deref().unwrap_or("N/A")
-        ));
-        output.push_str(&format!(
-            "- **Branch**: {}\n",
-            git_info.branch.as_deref().unwrap_or("N/A")
-        ));
+        output.push_str("<h1>Repository Context</h1>\n");
+        output.push_str(&dump_metadata_html(context));
+
+        if !context.tree_repr.is_empty() {
+            output.push_str("<section id=\"tree\">\n<h2>Directory Structure</h2>\n<pre>");
+            output.push_str(&html_escape(&context.tree_repr));
+            output.push_str("</pre>\n</section>\n");
+        }
+
+        for file in &context.file_ctx.file_entries {
+            drain(&mut output, out)?;
+            output.push_str(&dump_file_entry_html(file, config, &context.root_path));
+        }
+
+        let entries = &context.file_ctx.file_entries;
+        output.push_str("<section id=\"summary\">\n<h2>Summary</h2>\n<ul>\n");
+        output.push_str(&format!("<li>Total files: {}</li>\n", entries.len()));
         output.push_str(&format!(
-            "- **Author**: {} <{}>\n",
-            git_info.author.as_deref().unwrap_or("N/A"),
-            git_info.email.as_deref().unwrap_or("N/A")
+            "<li>Total size: {}</li>\n",
+            format_bytes(entries.iter().map(|f| f.size).sum())
         ));
         output.push_str(&format!(
-            "- **Date**: {}\n",
-            git_info.date.as_deref().unwrap_or("N/A")
+            "<li>Total lines: {}</li>\n",
+            group_thousands(entries.iter().map(|f| f.lines).sum())
         ));
-    } else {
-        output.push_str("Couldn't retrieve Git information.\n");
-    }
+        if let Some(limit) = config.skip_larger_than {
+            if context.file_ctx.skipped_by_size > 0 {
+                output.push_str(&format!(
+                    "<li>Files skipped by size (over {}): {}</li>\n",
+                    format_bytes(limit),
+                    context.file_ctx.skipped_by_size
+                ));
+            }
+        }
+        output.push_str("</ul>\n");
 
-    output
-}
+        let rows = language_breakdown_rows(entries, config.lang_breakdown_limit);
+        if !rows.is_empty() {
+            output.push_str("<h3>Language breakdown (by extension)</h3>\n<ul>\n");
+            for (label, (files, lines, bytes)) in rows {
+                output.push_str(&format!(
+                    "<li>{}: {} file(s), {} lines, {}</li>\n",
+                    html_escape(&label),
+                    files,
+                    group_thousands(lines),
+                    format_bytes(bytes)
+                ));
+            }
+            output.push_str("</ul>\n");
+        }
 
-fn dump_file_context_summary(file_context: &FileContext) -> String {
-    let mut output = String::new();
-    output.push_str(&format!(
-        "Total files indexed: {}\n",
-        file_context.file_entries.len()
-    ));
+        if !context.file_ctx.skipped.is_empty() {
+            output.push_str("<h3>Skipped files</h3>\n<ul>\n");
+            for (path, reason) in &context.file_ctx.skipped {
+                output.push_str(&format!(
+                    "<li>{}: {}</li>\n",
+                    html_escape(path),
+                    html_escape(reason)
+                ));
+            }
+            output.push_str("</ul>\n");
+        }
+        output.push_str("</section>\n</body>\n</html>\n");
 
-    let total_size: u64 = file_context.file_entries.iter().map(|f| f.size).sum();
-    output.push_str(&format!(
-        "Total size of files: {:.2} MB\n",
-        total_size as f64 / 1_048_576.0
-    ));
+        drain(&mut output, out)
+    }
 
-    let total_lines: u64 = file_context.file_entries.iter().map(|f| f.lines).sum();
-    output.push_str(&format!("Total lines across all files: {}\n", total_lines));
+    /// Format as JSON Lines
+    fn format_jsonl(&self, context: &RepositoryContext) -> String {
+        let mut buffer = Vec::new();
+        self.write_jsonl(context, &mut buffer)
+            .expect("writing to an in-memory buffer cannot fail");
+        String::from_utf8(buffer).expect("JSON output is valid UTF-8")
+    }
 
-    // Language breakdown (by file extension)
-    use std::collections::HashMap;
-    let mut lang_counts: HashMap<String, (u64, u64, u64)> = HashMap::new();
+    /// Write JSON Lines: a metadata object on the first line, then one serialized
+    /// `FileEntry` per line, each written out as soon as it is serialized
+    fn write_jsonl<W: Write>(
+        &self,
+        context: &RepositoryContext,
+        out: &mut W,
+    ) -> std::io::Result<()> {
+        let config = &context.file_ctx.config;
+        let git_info = &context.git_info;
+        let entries = &context.file_ctx.file_entries;
 
-    for f in &file_context.file_entries {
-        // Use extension as a proxy for language (simple heuristic)
-        let ext = match f.path.rsplit('.').next() {
-            Some(seg) if seg != f.path => seg.to_lowercase(),
-            _ => String::from(""),
+        let metadata = JsonlMetadata {
+            root_path: (!config.stable).then_some(context.root_path.as_str()),
+            commit_hash: git_info.commit_hash.as_deref(),
+            branch: git_info.branch.as_deref(),
+            files: entries.len(),
+            total_size: entries.iter().map(|f| f.size).sum(),
+            total_lines: entries.iter().map(|f| f.lines).sum(),
         };
+        serde_json::to_writer(&mut *out, &metadata)?;
+        out.write_all(b"\n")?;
 
-        let entry = lang_counts.entry(ext).or_insert((0, 0, 0));
-        // (files, lines, bytes)
-        entry.0 += 1;
-        entry.1 += f.lines;
-        entry.2 += f.size;
+        for file in entries {
+            // Modification times differ between checkouts, so --stable leaves them out
+            let file = if config.stable && file.modified.is_some() {
+                Cow::Owned(FileEntry {
+                    modified: None,
+                    ..file.clone()
+                })
+            } else {
+                Cow::Borrowed(file)
+            };
+            serde_json::to_writer(&mut *out, file.as_ref())?;
+            out.write_all(b"\n")?;
+        }
+        Ok(())
     }
 
-    if !lang_counts.is_empty() {
-        // Sort by total lines desc
-        let mut items: Vec<(String, (u64, u64, u64))> = lang_counts.into_iter().collect();
-        items.sort_by(|a, b| b.1 .1.cmp(&a.1 .1));
+    /// Format as JSON
+    #[allow(unused_variables, dead_code)]
+    fn format_json(&self, context: &RepositoryContext) -> String {
+        todo!("Format as JSON NYI")
+    }
+}
 
-        output.push_str("\n### Language breakdown (by extension)\n\n");
-        for (ext, (files, lines, bytes)) in items.iter().take(10) {
-            let pct = if total_lines > 0 {
-                (*lines as f64 / total_lines as f64) * 100.0
-            } else {
-                0.0
-            };
-            let label = if ext.is_empty() { "(no-ext)" } else { ext };
-            output.push_str(&format!(
-                "- {}: {} file(s), {} lines ({:.1}%), {:.2} MB\n",
-                label,
-                files,
-                lines,
-                pct,
-                *bytes as f64 / 1_048_576.0
-            ));
+/// Write a buffered output file, gzip-compressed when `gzip` is set
+fn write_output_file(path: &str, bytes: &[u8], gzip: bool) -> std::io::Result<()> {
+    let mut file = OutputFile::create(path, gzip)?;
+    file.write_all(bytes)?;
+    file.finish()
+}
+
+/// Output file, optionally gzip-compressed
+enum OutputFile {
+    Plain(std::fs::File),
+    Gzip(GzEncoder<std::fs::File>),
+}
+
+impl OutputFile {
+    fn create(path: &str, gzip: bool) -> std::io::Result<Self> {
+        let file = std::fs::File::create(path)?;
+        Ok(match gzip {
+            true => OutputFile::Gzip(GzEncoder::new(file, Compression::default())),
+            false => OutputFile::Plain(file),
+        })
+    }
+
+    /// Flush the file. For gzip this writes the trailer, which dropping the encoder would
+    /// do without reporting errors.
+    fn finish(self) -> std::io::Result<()> {
+        match self {
+            OutputFile::Plain(mut file) => file.flush(),
+            OutputFile::Gzip(encoder) => encoder.finish().map(|_| ()),
         }
     }
+}
 
-    // Top files by line count (quick hotspot view)
-    let mut files_sorted = file_context.file_entries.clone();
-    files_sorted.sort_by(|a, b| b.lines.cmp(&a.lines).then_with(|| a.path.cmp(&b.path)));
+impl Write for OutputFile {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        match self {
+            OutputFile::Plain(file) => file.write(buf),
+            OutputFile::Gzip(encoder) => encoder.write(buf),
+        }
+    }
 
-    output.push_str("\n### Top files by lines\n\n");
-    for f in files_sorted.iter().take(10) {
-        output.push_str(&format!(
-            "- {}: {} lines, {:.2} KB\n",
-            f.path,
-            f.lines,
-            f.size as f64 / 1024.0
-        ));
+    fn flush(&mut self) -> std::io::Result<()> {
+        match self {
+            OutputFile::Plain(file) => file.flush(),
+            OutputFile::Gzip(encoder) => encoder.flush(),
+        }
     }
+}
 
-    output
+/// Writer adapter that copies everything to two writers
+struct TeeWriter<A: Write, B: Write> {
+    first: A,
+    second: B,
 }
 
-fn dump_separator_md() -> String {
-    let mut output = String::new();
-    output.push_str("--------------------------------------------\n\n");
-    output
+impl<A: Write, B: Write> Write for TeeWriter<A, B> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        // Both sides have to receive the same bytes, so no partial writes
+        self.first.write_all(buf)?;
+        self.second.write_all(buf)?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.first.flush()?;
+        self.second.flush()
+    }
 }
 
-/// Detect programming language from file path/extension
-fn get_file_extension(file_path: &str) -> &str {
-    // Get file extension efficiently
-    if let Some(dot_pos) = file_path.rfind('.') {
-        &file_path[dot_pos + 1..]
-    } else {
-        ""
+/// Put `text` on the system clipboard. Fails with a readable error on headless systems.
+#[cfg(feature = "clipboard")]
+fn copy_to_clipboard(text: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let mut clipboard = arboard::Clipboard::new()
+        .map_err(|e| format!("System clipboard is not available: {}", e))?;
+    clipboard
+        .set_text(text)
+        .map_err(|e| format!("Failed to copy the output to the clipboard: {}", e))?;
+    Ok(())
+}
+
+#[cfg(not(feature = "clipboard"))]
+fn copy_to_clipboard(_text: &str) -> Result<(), Box<dyn std::error::Error>> {
+    Err("Clipboard output requires building with the `clipboard` feature".into())
+}
+
+/// Fence language of embedded binary files, None when they are skipped
+fn binary_encoding(config: &Config) -> Option<&'static str> {
+    match config.binary_mode {
+        BinaryMode::Skip => None,
+        BinaryMode::Base64 => Some("base64"),
+        BinaryMode::Hex => Some("hex"),
     }
 }
 
-fn dump_tree_structure(ctx_manager: &ContextManager) -> String {
-    let mut output = String::new();
+/// Encode binary file bytes for embedding, wrapped into lines that end with a newline
+fn encode_binary(bytes: &[u8], mode: BinaryMode) -> String {
+    use base64::Engine;
 
-    let tree_str = get_tree_structure(ctx_manager);
+    let (encoded, width) = match mode {
+        BinaryMode::Skip => return String::new(),
+        // Same line length as MIME base64
+        BinaryMode::Base64 => (base64::engine::general_purpose::STANDARD.encode(bytes), 76),
+        // 32 bytes per line
+        BinaryMode::Hex => (
+            bytes
+                .iter()
+                .map(|b| format!("{:02x}", b))
+                .collect::<String>(),
+            64,
+        ),
+    };
 
-    // dump tree structure
-    if !tree_str.is_empty() {
-        output.push_str("## Directory Structure\n\n");
-        output.push_str("```\n");
-        output.push_str(&tree_str);
-        output.push_str("```\n\n");
+    let mut output = String::with_capacity(encoded.len() + encoded.len() / width + 1);
+    for line in encoded.as_bytes().chunks(width) {
+        output.push_str(std::str::from_utf8(line).expect("encoded output is ASCII"));
+        output.push('\n');
     }
+    output
+}
 
-    output.push_str(&dump_separator_md());
+/// Listing printed by `--dry-run`: one line per file that would be packaged with its size and
+/// line count, followed by the totals
+pub fn dry_run_report(file_ctx: &FileContext) -> String {
+    let entries = &file_ctx.file_entries;
+    let width = entries.iter().map(|f| f.path.len()).max().unwrap_or(0);
+
+    let mut output = String::new();
+    for file in entries {
+        output.push_str(&format!(
+            "{:<width$}  {:>10}  {:>8} lines\n",
+            file.path,
+            format_bytes(file.size),
+            group_thousands(file.lines)
+        ));
+    }
+
+    let total_size: u64 = entries.iter().map(|f| f.size).sum();
+    let total_lines: u64 = entries.iter().map(|f| f.lines).sum();
+    output.push_str(&format!(
+        "{} file(s), {}, {} lines\n",
+        entries.len(),
+        format_bytes(total_size),
+        group_thousands(total_lines)
+    ));
     output
 }
 
-fn get_tree_structure(ctx_manager: &ContextManager) -> String {
-    // Cloning could be very expensive for large trees
-    // We'll afford it for now, but consider refactoring later
-    ctx_manager.context.as_ref().unwrap().tree_repr.clone()
+/// Summarize the size of a generated output, e.g. `Output size: 1.2 MB, 3,400 lines`
+pub fn output_size_report(buffer: &str) -> String {
+    size_report(buffer.len() as u64, buffer.lines().count() as u64)
+}
+
+/// Render one file section of the markdown report: the `FILE:` heading followed by the
+/// fenced content, or a note when the content is not available. Paths are printed as stored.
+///
+/// ```
+/// use rusty_repo_context_manager::output::render_file_entry;
+/// use rusty_repo_context_manager::{FileEntry, LineEnding};
+///
+/// let file = FileEntry {
+///     path: "src/lib.rs".to_string(),
+///     content: Some("pub fn answer() -> u32 {\n    42\n}\n".to_string()),
+///     size: 34,
+///     lines: 3,
+///     line_ending: LineEnding::Lf,
+///     tokens: 0,
+///     is_binary: false,
+///     last_commit_time: None,
+///     last_commit: None,
+///     modified: None,
+///     source_path: "src/lib.rs".into(),
+///     line_range: None,
+///     changed: None,
+///     content_hash: None,
+///     content_lossy: false,
+///     raw_bytes: None,
+///     omitted_reason: None,
+/// };
+///
+/// let section = render_file_entry(&file, true);
+/// assert!(section.starts_with("## FILE: src/lib.rs (34 bytes)"));
+/// assert!(section.contains("```rust\n"));
+/// assert!(section.contains("3: }"));
+/// ```
+pub fn render_file_entry(file: &FileEntry, show_line_numbers: bool) -> String {
+    let config = Config {
+        show_line_numbers,
+        ..Config::default()
+    };
+    dump_file_entry(file, &config, "", None)
+}
+
+/// Render the bullet list of the "Git Information" section of the markdown report
+pub fn render_git_info(git_info: &GitInfo, config: &Config) -> String {
+    dump_git_info_md(git_info, config)
+}
+
+/// Render the body of the "Summary" section of the markdown report
+pub fn render_file_summary(file_context: &FileContext) -> String {
+    dump_file_context_summary(file_context)
+}
+
+fn size_report(bytes: u64, lines: u64) -> String {
+    format!(
+        "Output size: {}, {} lines",
+        format_bytes(bytes),
+        group_thousands(lines)
+    )
+}
+
+/// Move everything rendered so far into `out`
+fn drain<W: Write>(output: &mut String, out: &mut W) -> std::io::Result<()> {
+    out.write_all(output.as_bytes())?;
+    output.clear();
+    Ok(())
+}
+
+/// Writer adapter that passes through at most `remaining` bytes, then fails so rendering stops.
+/// Writes are whole rendered strings, so the cut is moved back to a UTF-8 character boundary.
+struct LimitedWriter<W: Write> {
+    inner: W,
+    remaining: u64,
+    truncated: bool,
+}
+
+impl<W: Write> LimitedWriter<W> {
+    fn new(inner: W, limit: u64) -> Self {
+        Self {
+            inner,
+            remaining: limit,
+            truncated: false,
+        }
+    }
+}
+
+impl<W: Write> Write for LimitedWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        if (buf.len() as u64) <= self.remaining {
+            self.inner.write_all(buf)?;
+            self.remaining -= buf.len() as u64;
+            return Ok(buf.len());
+        }
+
+        let mut cut = self.remaining as usize;
+        while cut > 0 && (buf[cut] & 0b1100_0000) == 0b1000_0000 {
+            cut -= 1;
+        }
+        self.inner.write_all(&buf[..cut])?;
+        self.remaining = 0;
+        self.truncated = true;
+        Err(std::io::Error::other("output size limit reached"))
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Writer adapter that tracks how much was written, for `--report-output-size` while streaming
+struct CountingWriter<W: Write> {
+    inner: W,
+    bytes: u64,
+    newlines: u64,
+    ends_with_newline: bool,
+}
+
+impl<W: Write> CountingWriter<W> {
+    fn new(inner: W) -> Self {
+        Self {
+            inner,
+            bytes: 0,
+            newlines: 0,
+            ends_with_newline: true,
+        }
+    }
+
+    /// Line count matching `str::lines` on the written content
+    fn lines(&self) -> u64 {
+        self.newlines + u64::from(!self.ends_with_newline)
+    }
+}
+
+impl<W: Write> Write for CountingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        let written_bytes = &buf[..written];
+        self.bytes += written as u64;
+        self.newlines += written_bytes.iter().filter(|b| **b == b'\n').count() as u64;
+        if let Some(last) = written_bytes.last() {
+            self.ends_with_newline = *last == b'\n';
+        }
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Human readable byte count using 1024-based units
+fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 3] = ["KB", "MB", "GB"];
+    if bytes < 1024 {
+        return format!("{} B", bytes);
+    }
+
+    let mut value = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", value, UNITS[unit])
+}
+
+/// Format a number with `,` thousands separators
+fn group_thousands(n: u64) -> String {
+    let digits = n.to_string();
+    let mut grouped = String::new();
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            grouped.push(',');
+        }
+        grouped.push(c);
+    }
+    grouped
+}
+
+/// Split entries into consecutive groups for rendering. With `by_dir`, files are grouped by
+/// parent directory (in order of first appearance); otherwise everything is a single group.
+fn group_entries<'a>(
+    entries: &'a [FileEntry],
+    config: &Config,
+) -> Vec<(String, Vec<&'a FileEntry>)> {
+    if config.group_by == Some(GroupBy::Role) {
+        return group_by_role(entries)
+            .into_iter()
+            .map(|(role, files)| (role.label().to_string(), files))
+            .collect();
+    }
+    if !config.dir_summaries {
+        return vec![(String::new(), entries.iter().collect())];
+    }
+
+    let mut groups: Vec<(String, Vec<&FileEntry>)> = Vec::new();
+    for file in entries {
+        let dir = match file.path.rsplit_once('/') {
+            Some((dir, _)) => format!("{}/", dir),
+            None => "./".to_string(),
+        };
+        match groups.iter_mut().find(|(name, _)| *name == dir) {
+            Some((_, files)) => files.push(file),
+            None => groups.push((dir, vec![file])),
+        }
+    }
+    groups
+}
+
+/// Non-empty role groups in `FileRole::ALL` order, keeping the discovery order within a group
+fn group_by_role(entries: &[FileEntry]) -> Vec<(FileRole, Vec<&FileEntry>)> {
+    FileRole::ALL
+        .iter()
+        .map(|role| {
+            let files: Vec<&FileEntry> = entries
+                .iter()
+                .filter(|f| file_role(&f.path) == *role)
+                .collect();
+            (*role, files)
+        })
+        .filter(|(_, files)| !files.is_empty())
+        .collect()
+}
+
+/// Compact summary line (files, lines, languages) for one directory's files
+fn dump_dir_summary(dir: &str, files: &[&FileEntry]) -> String {
+    let entries: Vec<FileEntry> = files.iter().map(|f| (*f).clone()).collect();
+    let lines: u64 = entries.iter().map(|f| f.lines).sum();
+    let languages: Vec<String> = language_breakdown(&entries)
+        .into_iter()
+        .map(|(ext, (count, _, _))| {
+            let name = match language_name(&ext) {
+                Some(name) => name.to_string(),
+                None if ext.is_empty() => "(no-ext)".to_string(),
+                None => ext,
+            };
+            format!("{} ({})", name, count)
+        })
+        .collect();
+
+    format!(
+        "*Directory summary for {}: {} file(s), {} lines; {}*\n\n",
+        dir,
+        entries.len(),
+        lines,
+        languages.join(", ")
+    )
+}
+
+/// Write the structured directory tree to `path` as pretty-printed JSON
+fn write_tree_json(
+    context: &RepositoryContext,
+    path: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let tree = context
+        .tree
+        .as_ref()
+        .ok_or("Tree structure not available for --tree-json")?;
+    let json = serde_json::to_string_pretty(tree)?;
+    std::fs::write(path, json)
+        .map_err(|e| format!("Failed to write tree JSON to {}: {}", path, e))?;
+    Ok(())
+}
+
+fn dump_file_entry(
+    file: &FileEntry,
+    config: &Config,
+    root_path: &str,
+    size_rank: Option<&SizeRank>,
+) -> String {
+    let mut output = String::new();
+
+    let mut header = format!(
+        "{} FILE: {}",
+        heading_marker(2, config.heading_offset),
+        display_path(&file.path, config, root_path)
+    );
+    // Include file size in bytes in the file header when available
+    if file.size > 0 {
+        header.push_str(&format!(" ({} bytes)", file.size));
+    }
+    if let Some((start, end)) = file.line_range {
+        header.push_str(&format!(" (lines {}-{})", start, end));
+    }
+    if let Some(rank) = size_rank {
+        header.push_str(&format!(
+            " (rank {} of {} by size, {} percentile)",
+            rank.rank,
+            rank.total,
+            ordinal(rank.percentile)
+        ));
+    }
+    if config.show_file_age {
+        header.push_str(&format!(
+            " [last change: {}]",
+            format_file_age(file, config.stable)
+        ));
+    }
+    if let Some(hash) = &file.last_commit {
+        header.push_str(&format!(" [commit {}]", &hash[..hash.len().min(7)]));
+    }
+    // Modification times differ between checkouts, so --stable leaves them out
+    if let Some(modified) = file.modified.filter(|_| !config.stable) {
+        header.push_str(&format!(" [modified {}]", format_timestamp(modified)));
+    }
+    if config.complexity {
+        if let Some(content) = &file.content {
+            header.push_str(&format!(" (complexity {})", complexity_score(content)));
+        }
+    }
+    output.push_str(&header);
+    output.push_str("\n\n");
+
+    if config.front_matter {
+        output.push_str(&dump_front_matter(file));
+    }
+
+    if file.content_lossy {
+        output.push_str(
+            "*Note: file contains invalid text encoding, undecodable bytes were replaced*\n\n",
+        );
+    }
+
+    match file.line_ending {
+        LineEnding::Lf => {}
+        LineEnding::CrLf => output.push_str("*Note: file uses CRLF line endings*\n\n"),
+        LineEnding::Mixed => output.push_str("*Note: file mixes LF and CRLF line endings*\n\n"),
+    }
+
+    if config.metadata_only {
+        // Also covers content swapped in later, e.g. by --staged
+        output.push_str("*Content omitted (metadata-only)*\n");
+    } else if let Some(content) = &file.content {
+        let content = prepare_content(content, &file.path, config);
+        let content = content.as_ref();
+        let language = fence_language(&file.path);
+        let fence = code_fence(content);
+        output.push_str(&format!("{}{}\n", fence, language));
+
+        if config.show_line_numbers {
+            // One numbered line per counted line, so the numbers end at `FileEntry::lines`
+            // (offset to the file's own numbering for a line range).
+            // Right-align numbers to the widest one so the code stays in one column
+            let first = first_line_number(file);
+            let width = (first + count_lines(content).max(1) as usize - 1)
+                .to_string()
+                .len();
+            for (i, line) in content.lines().enumerate() {
+                output.push_str(&config.line_number_style.format_line(first + i, width, line));
+                output.push('\n');
+            }
+        } else {
+            output.push_str(content);
+            if !content.ends_with('\n') {
+                output.push('\n');
+            }
+        }
+
+        output.push_str(&fence);
+        output.push('\n');
+    } else if let Some(reason) = &file.omitted_reason {
+        output.push_str(&format!("*Content omitted: {}*\n", reason));
+    } else if let Some((bytes, encoding)) = file.raw_bytes.as_ref().zip(binary_encoding(config)) {
+        output.push_str(&format!("```{}\n", encoding));
+        output.push_str(&encode_binary(bytes, config.binary_mode));
+        output.push_str("```\n");
+    } else if file.is_binary {
+        output.push_str("*Binary file - content not displayed*\n");
+    } else {
+        output.push_str("*Content not available*\n");
+    }
+
+    output
+}
+
+/// Output file name (without extension) for `--split-by-ext`: the lowercase extension of the
+/// file name, or `no-ext`. Anything but letters, digits, `-` and `_` becomes `_`, so the name
+/// always stays inside the output directory.
+fn split_bucket(path: &str) -> String {
+    match Path::new(path).extension().map(|ext| ext.to_string_lossy()) {
+        Some(ext) if !ext.is_empty() => ext
+            .to_lowercase()
+            .chars()
+            .map(|c| match c.is_alphanumeric() || c == '-' || c == '_' {
+                true => c,
+                false => '_',
+            })
+            .collect(),
+        _ => String::from("no-ext"),
+    }
+}
+
+/// Number of the first line of the content, 1 unless only a line range was read
+fn first_line_number(file: &FileEntry) -> usize {
+    file.line_range.map_or(1, |(start, _)| start as usize)
+}
+
+/// First line of the JSON Lines output
+#[derive(Serialize)]
+struct JsonlMetadata<'a> {
+    // Left out with `--stable`, like the location in the other formats
+    #[serde(skip_serializing_if = "Option::is_none")]
+    root_path: Option<&'a str>,
+    commit_hash: Option<&'a str>,
+    branch: Option<&'a str>,
+    files: usize,
+    total_size: u64,
+    total_lines: u64,
+}
+
+/// Header of a file whose content is identical to the already rendered file `first`
+fn dump_duplicate_entry(file: &FileEntry, first: &str, config: &Config, root_path: &str) -> String {
+    format!(
+        "{} FILE: {} ({} bytes)\n\n*Duplicate of {}*\n",
+        heading_marker(2, config.heading_offset),
+        display_path(&file.path, config, root_path),
+        file.size,
+        display_path(first, config, root_path)
+    )
+}
+
+/// Per-file metadata emitted as YAML front matter
+#[derive(Serialize)]
+struct FrontMatter<'a> {
+    path: &'a str,
+    size: u64,
+    lines: u64,
+    language: String,
+    binary: bool,
+}
+
+/// Render a `---` delimited YAML front matter block for a file
+fn dump_front_matter(file: &FileEntry) -> String {
+    let ext = extension_key(&file.path);
+    let front_matter = FrontMatter {
+        path: &file.path,
+        size: file.size,
+        lines: file.lines,
+        language: language_name(&ext).map(str::to_string).unwrap_or(ext),
+        binary: file.is_binary,
+    };
+
+    match serde_yaml_ng::to_string(&front_matter) {
+        Ok(yaml) => format!("---\n{}---\n\n", yaml),
+        Err(e) => {
+            eprintln!(
+                "Warning: Could not serialize front matter for {}: {}",
+                file.path, e
+            );
+            String::new()
+        }
+    }
+}
+
+/// Position of a file when all files are ordered by size
+struct SizeRank {
+    /// 1 is the largest file; equally sized files share a rank
+    rank: usize,
+    total: usize,
+    /// Share of files that are the same size or smaller
+    percentile: usize,
+}
+
+fn compute_size_ranks(entries: &[FileEntry]) -> HashMap<&str, SizeRank> {
+    let total = entries.len();
+    let mut sizes: Vec<u64> = entries.iter().map(|f| f.size).collect();
+    sizes.sort_unstable();
+
+    entries
+        .iter()
+        .map(|f| {
+            let smaller_or_equal = sizes.partition_point(|&s| s <= f.size);
+            let rank = total - smaller_or_equal + 1;
+            let percentile = smaller_or_equal * 100 / total;
+            (
+                f.path.as_str(),
+                SizeRank {
+                    rank,
+                    total,
+                    percentile,
+                },
+            )
+        })
+        .collect()
+}
+
+/// Paths of the files that do not fit in a token budget. Files are packed smallest first (ties
+/// broken by path) and packing stops at the first file that would exceed the budget.
+fn files_over_token_budget(entries: &[FileEntry], budget: u64) -> HashSet<&str> {
+    let mut by_tokens: Vec<&FileEntry> = entries.iter().collect();
+    by_tokens.sort_by(|a, b| a.tokens.cmp(&b.tokens).then_with(|| a.path.cmp(&b.path)));
+
+    let mut total = 0;
+    let fitting = by_tokens
+        .iter()
+        .take_while(|f| {
+            total += f.tokens;
+            total <= budget
+        })
+        .count();
+    by_tokens[fitting..]
+        .iter()
+        .map(|f| f.path.as_str())
+        .collect()
+}
+
+/// Note listing the files left out to stay within `--max-tokens`, in output order
+fn dump_token_budget_note(entries: &[FileEntry], omitted: &HashSet<&str>, budget: u64) -> String {
+    if omitted.is_empty() {
+        return String::new();
+    }
+
+    let mut output = format!(
+        "*Token budget of {} reached, {} file(s) omitted:*\n\n",
+        group_thousands(budget),
+        omitted.len()
+    );
+    for f in entries.iter().filter(|f| omitted.contains(f.path.as_str())) {
+        output.push_str(&format!("- {} (~{} tokens)\n", f.path, f.tokens));
+    }
+    output.push('\n');
+
+    output
+}
+
+/// English ordinal for a number, e.g. `1st`, `42nd`, `95th`
+fn ordinal(n: usize) -> String {
+    let suffix = match (n % 10, n % 100) {
+        (_, 11..=13) => "th",
+        (1, _) => "st",
+        (2, _) => "nd",
+        (3, _) => "rd",
+        _ => "th",
+    };
+    format!("{}{}", n, suffix)
+}
+
+/// Render a stored (root-relative) path according to the configured path style
+fn display_path(path: &str, config: &Config, root_path: &str) -> String {
+    match config.path_style {
+        PathStyle::Relative => path.to_string(),
+        PathStyle::Absolute => Path::new(root_path)
+            .join(path)
+            .to_string_lossy()
+            .to_string(),
+        PathStyle::Repo => format!("./{}", path),
+    }
+}
+
+/// Backtick fence long enough to wrap `content`: one longer than the longest backtick run
+/// inside it, and at least three (CommonMark fenced code blocks)
+fn code_fence(content: &str) -> String {
+    let longest_run = content.split(|c| c != '`').map(str::len).max().unwrap_or(0);
+    "`".repeat((longest_run + 1).max(3))
+}
+
+/// Apply render-time transformations to file content. The stored content is left untouched.
+fn prepare_content<'a>(content: &'a str, path: &str, config: &Config) -> Cow<'a, str> {
+    let content = match config.skeleton {
+        true => skeletonize(content, &extension_key(path))
+            .map(Cow::Owned)
+            .unwrap_or(Cow::Borrowed(content)),
+        false => Cow::Borrowed(content),
+    };
+
+    let content = match config.stable && content.contains('\r') {
+        true => Cow::Owned(content.replace("\r\n", "\n")),
+        false => content,
+    };
+
+    let content = match config.expand_tabs {
+        Some(width) if content.contains('\t') => {
+            Cow::Owned(expand_tabs(&content, width, config.tabs_leading_only))
+        }
+        _ => content,
+    };
+
+    match config.max_line_width {
+        Some(width) => truncate_long_lines(content, width),
+        None => content,
+    }
+}
+
+/// Start of the HTML page, with the little styling the page needs inlined
+const HTML_HEAD: &str = r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<title>Repository Context</title>
+<style>
+body { font-family: sans-serif; max-width: 60rem; margin: 2rem auto; padding: 0 1rem; }
+pre { background: #f6f8fa; padding: 0.75rem; overflow-x: auto; }
+.meta { color: #57606a; }
+</style>
+</head>
+<body>
+"#;
+
+/// Escape the characters that are significant in HTML text and attribute values
+fn html_escape(text: &str) -> Cow<'_, str> {
+    if !text.contains(['&', '<', '>', '"']) {
+        return Cow::Borrowed(text);
+    }
+
+    let mut escaped = String::with_capacity(text.len() + 16);
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            _ => escaped.push(c),
+        }
+    }
+    Cow::Owned(escaped)
+}
+
+/// Metadata section of the HTML page, with the same git fields as the markdown output
+fn dump_metadata_html(context: &RepositoryContext) -> String {
+    let config = &context.file_ctx.config;
+    let git_info = &context.git_info;
+    let mut items: Vec<(&str, String)> = Vec::new();
+
+    if !config.stable {
+        items.push(("Location", context.root_path.clone()));
+    }
+    if git_info.is_repo {
+        let field = |value: &Option<String>| value.as_deref().unwrap_or("N/A").to_string();
+        items.push(("Commit Hash", field(&git_info.commit_hash)));
+        items.push(("Branch", field(&git_info.branch)));
+        if let Some(describe) = &git_info.describe {
+            items.push(("Describe", describe.clone()));
+        }
+        if let Some(remote_url) = &git_info.remote_url {
+            items.push(("Remote", remote_url.clone()));
+        }
+        items.push((
+            "Author",
+            format!("{} <{}>", field(&git_info.author), field(&git_info.email)),
+        ));
+        items.push(("Date", field(&git_info.date)));
+        for sub in &git_info.submodules {
+            items.push((
+                "Submodule",
+                format!(
+                    "{} at {}: {} @ {}",
+                    sub.name,
+                    sub.path,
+                    sub.url.as_deref().unwrap_or("no url"),
+                    sub.commit.as_deref().unwrap_or("not committed")
+                ),
+            ));
+        }
+    } else {
+        items.push(("Git", "Not a git repository".to_string()));
+    }
+
+    let mut output = String::from("<section id=\"metadata\">\n<h2>Metadata</h2>\n<ul>\n");
+    for (label, value) in items {
+        output.push_str(&format!(
+            "<li><strong>{}</strong>: {}</li>\n",
+            label,
+            html_escape(&value)
+        ));
+    }
+    output.push_str("</ul>\n</section>\n");
+    output
+}
+
+/// One file of the HTML page: a heading with the path and an escaped code block
+fn dump_file_entry_html(file: &FileEntry, config: &Config, root_path: &str) -> String {
+    let path = display_path(&file.path, config, root_path);
+    let mut output = format!(
+        "<section class=\"file\">\n<h2>{}</h2>\n<p class=\"meta\">{}, {} lines</p>\n",
+        html_escape(&path),
+        format_bytes(file.size),
+        group_thousands(file.lines)
+    );
+
+    if config.metadata_only {
+        output.push_str("<p><em>Content omitted (metadata-only)</em></p>\n");
+    } else if let Some(content) = &file.content {
+        let content = prepare_content(content, &file.path, config);
+        let content = content.as_ref();
+        output.push_str(&format!(
+            "<pre><code class=\"language-{}\">",
+            html_escape(fence_language(&file.path))
+        ));
+        if config.show_line_numbers {
+            let first = first_line_number(file);
+            let width = (first + count_lines(content).max(1) as usize - 1)
+                .to_string()
+                .len();
+            for (i, line) in content.lines().enumerate() {
+                let line = config.line_number_style.format_line(first + i, width, line);
+                output.push_str(&html_escape(&line));
+                output.push('\n');
+            }
+        } else {
+            output.push_str(&html_escape(content));
+        }
+        output.push_str("</code></pre>\n");
+    } else if let Some(reason) = &file.omitted_reason {
+        output.push_str(&format!(
+            "<p><em>Content omitted: {}</em></p>\n",
+            html_escape(reason)
+        ));
+    } else if file.is_binary {
+        output.push_str("<p><em>Binary file - content not displayed</em></p>\n");
+    } else {
+        output.push_str("<p><em>Content not available</em></p>\n");
+    }
+
+    output.push_str("</section>\n");
+    output
+}
+
+/// Cut lines longer than `width` characters, replacing the rest of the line with a marker
+/// saying how many characters were left out. Line endings are kept.
+fn truncate_long_lines(content: Cow<'_, str>, width: usize) -> Cow<'_, str> {
+    let too_long = |line: &str| line.trim_end_matches(['\r', '\n']).chars().count() > width;
+    if !content.split_inclusive('\n').any(too_long) {
+        return content;
+    }
+
+    let mut output = String::with_capacity(content.len());
+    for line in content.split_inclusive('\n') {
+        let text = line.trim_end_matches(['\r', '\n']);
+        let length = text.chars().count();
+        if length <= width {
+            output.push_str(line);
+            continue;
+        }
+        output.extend(text.chars().take(width));
+        output.push_str(&format!("… (+{} chars)", length - width));
+        output.push_str(&line[text.len()..]);
+    }
+    Cow::Owned(output)
+}
+
+/// Replace tabs with spaces up to the next tab stop of `width` columns.
+/// With `leading_only`, tabs after the first non-whitespace character of a line are kept.
+fn expand_tabs(content: &str, width: usize, leading_only: bool) -> String {
+    let mut output = String::with_capacity(content.len());
+    let mut column = 0;
+    let mut in_indent = true;
+
+    for ch in content.chars() {
+        match ch {
+            '\n' => {
+                output.push(ch);
+                column = 0;
+                in_indent = true;
+            }
+            '\t' if in_indent || !leading_only => {
+                let spaces = if width == 0 {
+                    0
+                } else {
+                    width - column % width
+                };
+                output.extend(std::iter::repeat_n(' ', spaces));
+                column += spaces;
+            }
+            _ => {
+                if ch != ' ' && ch != '\t' {
+                    in_indent = false;
+                }
+                output.push(ch);
+                column += 1;
+            }
+        }
+    }
+
+    output
+}
+
+fn dump_repo_metadata_md(repo_context: &RepositoryContext) -> String {
+    let mut output = String::new();
+    // TODO(0.1): All matadata would be dumped here
+
+    let offset = repo_context.file_ctx.config.heading_offset;
+    output.push_str(&heading(2, offset, "Metadata"));
+    if !repo_context.file_ctx.config.stable {
+        output.push_str(&heading(3, offset, "File System Location"));
+        output.push_str(&format!("{}\n\n", repo_context.root_path));
+    }
+    output.push_str(&heading(3, offset, "Git Information"));
+    output.push_str(&dump_git_info_md(
+        &repo_context.git_info,
+        &repo_context.file_ctx.config,
+    ));
+    output.push_str(&dump_separator_md());
+    output
+}
+
+fn dump_git_info_md(git_info: &GitInfo, config: &Config) -> String {
+    let mut output = String::new();
+    let offset = config.heading_offset;
+
+    if git_info.is_repo {
+        output.push_str(&format!(
+            "- **Commit Hash**: {}\n",
+            git_info.commit_hash.as_deref().unwrap_or("N/A")
+        ));
+        output.push_str(&format!(
+            "- **Branch**: {}\n",
+            git_info.branch.as_deref().unwrap_or("N/A")
+        ));
+        if let Some(describe) = &git_info.describe {
+            output.push_str(&format!("- **Describe**: {}\n", describe));
+        }
+        if let Some(remote_url) = &git_info.remote_url {
+            output.push_str(&format!("- **Remote**: {}\n", remote_url));
+        }
+        output.push_str(&format!(
+            "- **Author**: {} <{}>\n",
+            git_info.author.as_deref().unwrap_or("N/A"),
+            git_info.email.as_deref().unwrap_or("N/A")
+        ));
+        output.push_str(&format!(
+            "- **Date**: {}\n",
+            git_info.date.as_deref().unwrap_or("N/A")
+        ));
+
+        if !git_info.commit_graph.is_empty() {
+            output.push('\n');
+            output.push_str(&heading(3, offset, "Commit Graph"));
+            output.push_str("```\n");
+            for line in &git_info.commit_graph {
+                output.push_str(line);
+                output.push('\n');
+            }
+            output.push_str("```\n");
+        }
+
+        if !git_info.recent_commits.is_empty() {
+            output.push('\n');
+            output.push_str(&heading(3, offset, "Recent Commits"));
+            for commit in &git_info.recent_commits {
+                output.push_str(&format!(
+                    "- `{}` {} ({}, {})\n",
+                    &commit.hash[..commit.hash.len().min(7)],
+                    commit.message,
+                    commit.author,
+                    commit.date
+                ));
+            }
+        }
+
+        if config.git_status {
+            output.push('\n');
+            output.push_str(&heading(3, offset, "Working Tree Changes"));
+            if git_info.status.is_empty() {
+                output.push_str("No uncommitted changes\n");
+            }
+            for (path, kind) in &git_info.status {
+                output.push_str(&format!("- {} ({})\n", path, kind.label()));
+            }
+        }
+
+        if !git_info.submodules.is_empty() {
+            output.push('\n');
+            output.push_str(&heading(3, offset, "Submodules"));
+            for sub in &git_info.submodules {
+                output.push_str(&format!(
+                    "- **{}** at `{}`: {} @ {}\n",
+                    sub.name,
+                    sub.path,
+                    sub.url.as_deref().unwrap_or("no url"),
+                    sub.commit.as_deref().unwrap_or("not committed")
+                ));
+            }
+        }
+
+        if let Some(range) = &git_info.range {
+            output.push_str(&dump_range_md(range, offset));
+        }
+    } else if !config.use_git {
+        output.push_str("Git information disabled (--no-git).\n");
+    } else {
+        output.push_str("Couldn't retrieve Git information.\n");
+    }
+
+    output
+}
+
+/// Render the commits and file changes of a `base..head` range
+fn dump_range_md(range: &RangeDiff, offset: usize) -> String {
+    let mut output = String::from("\n");
+    output.push_str(&heading(
+        3,
+        offset,
+        &format!("Range {}..{}", range.base, range.head),
+    ));
+
+    output.push_str(&heading(4, offset, "Commits"));
+    if range.commits.is_empty() {
+        output.push_str("No commits in range\n");
+    }
+    for commit in &range.commits {
+        output.push_str(&format!("- {}\n", commit));
+    }
+
+    for (title, paths) in [
+        ("Added files", &range.added),
+        ("Modified files", &range.modified),
+        ("Removed files", &range.removed),
+    ] {
+        if paths.is_empty() {
+            continue;
+        }
+        output.push('\n');
+        output.push_str(&heading(4, offset, title));
+        for path in paths {
+            output.push_str(&format!("- {}\n", path));
+        }
+    }
+
+    output
+}
+
+fn dump_file_context_summary(file_context: &FileContext) -> String {
+    let mut output = String::new();
+
+    if let Some(primary) = primary_language(&file_context.file_entries) {
+        output.push_str(&format!(
+            "Primary language: {} ({:.0}% of lines)\n",
+            primary.name, primary.percent
+        ));
+    }
+
+    output.push_str(&format!(
+        "Total files indexed: {}\n",
+        file_context.file_entries.len()
+    ));
+    if let Some(rev) = &file_context.config.changed_since {
+        if file_context.file_entries.is_empty() {
+            output.push_str(&format!("*No files changed since {}*\n", rev));
+        }
+    }
+    if file_context.config.redact {
+        output.push_str(&format!("Secrets redacted: {}\n", file_context.redactions));
+    }
+    if let Some(limit) = file_context.config.skip_larger_than {
+        if file_context.skipped_by_size > 0 {
+            output.push_str(&format!(
+                "Files skipped by size (over {}): {}\n",
+                format_bytes(limit),
+                file_context.skipped_by_size
+            ));
+        }
+    }
+
+    let total_size: u64 = file_context.file_entries.iter().map(|f| f.size).sum();
+    output.push_str(&format!(
+        "Total size of files: {:.2} MB\n",
+        total_size as f64 / 1_048_576.0
+    ));
+
+    let total_lines: u64 = file_context.file_entries.iter().map(|f| f.lines).sum();
+    output.push_str(&format!("Total lines across all files: {}\n", total_lines));
+
+    let total_tokens: u64 = file_context.file_entries.iter().map(|f| f.tokens).sum();
+    output.push_str(&format!(
+        "Total estimated tokens: {} (~4 characters per token)\n",
+        group_thousands(total_tokens)
+    ));
+
+    if file_context.config.reading_time {
+        let words: usize = file_context
+            .file_entries
+            .iter()
+            .filter_map(|f| f.content.as_deref())
+            .map(|content| content.split_whitespace().count())
+            .sum();
+        output.push_str(&format!(
+            "Estimated reading time: ~{} min\n",
+            words.div_ceil(WORDS_PER_MINUTE)
+        ));
+    }
+
+    // Language breakdown (by file extension)
+    let rows = language_breakdown_rows(
+        &file_context.file_entries,
+        file_context.config.lang_breakdown_limit,
+    );
+    if !rows.is_empty() {
+        output.push('\n');
+        output.push_str(&heading(
+            3,
+            file_context.config.heading_offset,
+            "Language breakdown (by extension)",
+        ));
+        let line = |label: &str, files: u64, lines: u64, bytes: u64| {
+            let pct = if total_lines > 0 {
+                (lines as f64 / total_lines as f64) * 100.0
+            } else {
+                0.0
+            };
+            format!(
+                "- {}: {} file(s), {} lines ({:.1}%), {:.2} MB\n",
+                label,
+                files,
+                lines,
+                pct,
+                bytes as f64 / 1_048_576.0
+            )
+        };
+        for (label, (files, lines, bytes)) in rows {
+            output.push_str(&line(&label, files, lines, bytes));
+        }
+    }
+
+    // Top files by line count (quick hotspot view)
+    let mut files_sorted = file_context.file_entries.clone();
+    files_sorted.sort_by(|a, b| b.lines.cmp(&a.lines).then_with(|| a.path.cmp(&b.path)));
+
+    output.push('\n');
+    output.push_str(&heading(
+        3,
+        file_context.config.heading_offset,
+        "Top files by lines",
+    ));
+    for f in files_sorted.iter().take(10) {
+        output.push_str(&format!(
+            "- {}: {} lines, {:.2} KB\n",
+            f.path,
+            f.lines,
+            f.size as f64 / 1024.0
+        ));
+    }
+
+    if file_context.config.complexity {
+        output.push_str(&dump_complexity_summary(file_context));
+    }
+
+    if file_context.config.group_by == Some(GroupBy::Role) {
+        output.push('\n');
+        output.push_str(&heading(
+            3,
+            file_context.config.heading_offset,
+            "Files by role",
+        ));
+        for (role, files) in group_by_role(&file_context.file_entries) {
+            output.push_str(&format!("- {}: {} file(s)\n", role.label(), files.len()));
+        }
+    }
+
+    if file_context.config.show_file_age {
+        output.push_str(&dump_file_age_summary(file_context));
+    }
+
+    let omitted: Vec<&FileEntry> = file_context
+        .file_entries
+        .iter()
+        .filter(|f| f.omitted_reason.is_some())
+        .collect();
+    if !omitted.is_empty() {
+        output.push('\n');
+        output.push_str(&heading(
+            3,
+            file_context.config.heading_offset,
+            "Omitted content",
+        ));
+        for f in omitted {
+            output.push_str(&format!(
+                "- {}: {}\n",
+                f.path,
+                f.omitted_reason.as_deref().unwrap_or_default()
+            ));
+        }
+    }
+
+    if !file_context.skipped.is_empty() {
+        output.push('\n');
+        output.push_str(&heading(
+            3,
+            file_context.config.heading_offset,
+            "Skipped files",
+        ));
+        for (path, reason) in &file_context.skipped {
+            output.push_str(&format!("- {}: {}\n", path, reason));
+        }
+    }
+
+    if file_context.config.todos {
+        output.push_str(&dump_action_items(file_context));
+    }
+
+    if file_context.config.whitespace_stats {
+        output.push_str(&dump_whitespace_summary(file_context));
+    }
+
+    if let Some(rev) = &file_context.config.focus_changes {
+        let changed = file_context
+            .file_entries
+            .iter()
+            .filter(|f| f.changed == Some(true))
+            .count();
+        output.push('\n');
+        output.push_str(&heading(
+            3,
+            file_context.config.heading_offset,
+            &format!("Changes since {}", rev),
+        ));
+        output.push_str(&format!("- Changed files: {}\n", changed));
+        output.push_str(&format!(
+            "- Unchanged files: {}\n",
+            file_context.file_entries.len() - changed
+        ));
+    }
+
+    output
+}
+
+/// List crate dependencies, marking dev-only ones
+fn dump_dependencies(dependencies: &[Dependency], offset: usize) -> String {
+    let mut output = String::from("\n");
+    output.push_str(&heading(3, offset, "Dependencies"));
+    if dependencies.is_empty() {
+        output.push_str("No Cargo.toml dependencies found\n");
+    }
+    for dep in dependencies {
+        output.push_str(&format!("- {}: {}", dep.name, dep.requirement));
+        if dep.dev {
+            output.push_str(" (dev)");
+        }
+        output.push('\n');
+    }
+
+    output
+}
+
+/// Markers searched by `--todos` when no custom list is given
+const DEFAULT_TODO_MARKERS: &[&str] = &["TODO", "FIXME", "HACK", "XXX"];
+
+/// List every line carrying an action marker (TODO, FIXME, ...) with its file and line number
+fn dump_action_items(file_context: &FileContext) -> String {
+    let markers: Vec<String> = if file_context.config.todo_markers.is_empty() {
+        DEFAULT_TODO_MARKERS
+            .iter()
+            .map(|m| m.to_lowercase())
+            .collect()
+    } else {
+        file_context
+            .config
+            .todo_markers
+            .iter()
+            .map(|m| m.to_lowercase())
+            .collect()
+    };
+
+    let mut output = String::from("\n");
+    output.push_str(&heading(
+        3,
+        file_context.config.heading_offset,
+        "Action items",
+    ));
+    let mut found = 0;
+    for file in &file_context.file_entries {
+        let Some(content) = &file.content else {
+            continue;
+        };
+        for (i, line) in content.lines().enumerate() {
+            let lowered = line.to_lowercase();
+            if markers.iter().any(|m| contains_word(&lowered, m)) {
+                output.push_str(&format!("- {}:{}: {}\n", file.path, i + 1, line.trim()));
+                found += 1;
+            }
+        }
+    }
+    if found == 0 {
+        output.push_str("No action items found\n");
+    }
+
+    output
+}
+
+/// Whether `word` occurs in `text` without being part of a longer identifier
+fn contains_word(text: &str, word: &str) -> bool {
+    let is_ident = |c: char| c.is_alphanumeric() || c == '_';
+    text.match_indices(word).any(|(start, _)| {
+        let before = text[..start].chars().next_back();
+        let after = text[start + word.len()..].chars().next();
+        !before.is_some_and(is_ident) && !after.is_some_and(is_ident)
+    })
+}
+
+/// Keywords that open a branch, each adding one path through the code
+const BRANCH_KEYWORDS: &[&str] = &["if", "for", "while", "match", "case"];
+
+/// Rough cyclomatic complexity: one plus the number of branch keywords and boolean operators.
+/// This is plain token counting, so it works for any language but also counts keywords in
+/// comments and strings.
+fn complexity_score(content: &str) -> u64 {
+    let keywords = content
+        .split(|c: char| !(c.is_alphanumeric() || c == '_'))
+        .filter(|word| BRANCH_KEYWORDS.contains(word))
+        .count();
+    let operators = content.matches("&&").count() + content.matches("||").count();
+    1 + (keywords + operators) as u64
+}
+
+/// Rank text files by complexity score, highest first
+fn dump_complexity_summary(file_context: &FileContext) -> String {
+    let mut scores: Vec<(&str, u64)> = file_context
+        .file_entries
+        .iter()
+        .filter_map(|f| Some((f.path.as_str(), complexity_score(f.content.as_deref()?))))
+        .collect();
+    scores.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(b.0)));
+
+    let mut output = String::from("\n");
+    output.push_str(&heading(
+        3,
+        file_context.config.heading_offset,
+        "Most complex files",
+    ));
+    for (path, score) in scores.iter().take(10) {
+        output.push_str(&format!("- {}: complexity {}\n", path, score));
+    }
+
+    output
+}
+
+/// Average human reading speed used for the reading time estimate
+const WORDS_PER_MINUTE: usize = 200;
+
+/// Aggregated (files, lines, bytes) per lowercase extension, sorted by lines descending.
+/// Ties are broken by extension name so the ordering is deterministic.
+fn language_breakdown(entries: &[FileEntry]) -> Vec<(String, (u64, u64, u64))> {
+    let mut lang_counts: HashMap<String, (u64, u64, u64)> = HashMap::new();
+
+    for f in entries {
+        let entry = lang_counts
+            .entry(extension_key(&f.path))
+            .or_insert((0, 0, 0));
+        // (files, lines, bytes)
+        entry.0 += 1;
+        entry.1 += f.lines;
+        entry.2 += f.size;
+    }
+
+    let mut items: Vec<(String, (u64, u64, u64))> = lang_counts.into_iter().collect();
+    items.sort_by(|a, b| b.1 .1.cmp(&a.1 .1).then_with(|| a.0.cmp(&b.0)));
+    items
+}
+
+/// Labelled rows of the language breakdown, keeping the first `limit` extensions (all of
+/// them when 0) and rolling the remaining ones up so the totals still add up
+fn language_breakdown_rows(entries: &[FileEntry], limit: usize) -> Vec<(String, (u64, u64, u64))> {
+    let items = language_breakdown(entries);
+    let limit = match limit {
+        0 => items.len(),
+        limit => limit.min(items.len()),
+    };
+    let (shown, rest) = items.split_at(limit);
+
+    let mut rows: Vec<(String, (u64, u64, u64))> = shown
+        .iter()
+        .map(|(ext, counts)| {
+            let label = if ext.is_empty() { "(no-ext)" } else { ext };
+            (label.to_string(), *counts)
+        })
+        .collect();
+    if !rest.is_empty() {
+        let counts = rest
+            .iter()
+            .fold((0, 0, 0), |(f, l, b), (_, (files, lines, bytes))| {
+                (f + files, l + lines, b + bytes)
+            });
+        rows.push((format!("Other ({} extensions)", rest.len()), counts));
+    }
+    rows
+}
+
+/// Dominant language of a set of files, measured by lines
+struct PrimaryLanguage {
+    name: String,
+    percent: f64,
+}
+
+fn primary_language(entries: &[FileEntry]) -> Option<PrimaryLanguage> {
+    let total_lines: u64 = entries.iter().map(|f| f.lines).sum();
+    if total_lines == 0 {
+        return None;
+    }
+
+    let (ext, (_, lines, _)) = language_breakdown(entries).into_iter().next()?;
+    let name = match language_name(&ext) {
+        Some(name) => name.to_string(),
+        None if ext.is_empty() => "(no-ext)".to_string(),
+        None => ext,
+    };
+
+    Some(PrimaryLanguage {
+        name,
+        percent: (lines as f64 / total_lines as f64) * 100.0,
+    })
+}
+
+/// Maximum number of top-level directories named in the overview
+const OVERVIEW_MAX_DIRS: usize = 5;
+
+/// One-paragraph, human readable overview derived from the summary stats and git info
+fn dump_overview(context: &RepositoryContext) -> String {
+    let entries = &context.file_ctx.file_entries;
+    let total_lines: u64 = entries.iter().map(|f| f.lines).sum();
+
+    let mut overview = format!(
+        "This repository contains {} files ({} lines)",
+        entries.len(),
+        total_lines
+    );
+    if let Some(primary) = primary_language(entries) {
+        overview.push_str(&format!(" primarily in {}", primary.name));
+    }
+    overview.push('.');
+
+    // Top-level directories, busiest first
+    let mut dirs: HashMap<&str, usize> = HashMap::new();
+    for file in entries {
+        if let Some((dir, _)) = file.path.split_once('/') {
+            *dirs.entry(dir).or_default() += 1;
+        }
+    }
+    let mut dirs: Vec<(&str, usize)> = dirs.into_iter().collect();
+    dirs.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+    if !dirs.is_empty() {
+        let names: Vec<String> = dirs
+            .iter()
+            .take(OVERVIEW_MAX_DIRS)
+            .map(|(dir, _)| format!("{}/", dir))
+            .collect();
+        overview.push_str(&format!(" Key directories: {}.", names.join(", ")));
+    }
+
+    if let Some(date) = &context.git_info.date {
+        overview.push_str(&format!(" Most recent change: {}.", date));
+    }
+
+    format!("{}\n\n", overview)
+}
+
+/// Line ending and whitespace hygiene counts over text files
+fn dump_whitespace_summary(file_context: &FileContext) -> String {
+    let (mut lf, mut crlf, mut mixed, mut trailing, mut no_final_newline) = (0, 0, 0, 0, 0);
+
+    for content in file_context
+        .file_entries
+        .iter()
+        .filter(|f| !f.is_binary)
+        .filter_map(|f| f.content.as_deref())
+    {
+        let crlf_count = content.matches("\r\n").count();
+        let lf_count = content.matches('\n').count() - crlf_count;
+        match (lf_count, crlf_count) {
+            (0, 0) => {}
+            (_, 0) => lf += 1,
+            (0, _) => crlf += 1,
+            _ => mixed += 1,
+        }
+
+        if content
+            .lines()
+            .any(|line| line.ends_with(' ') || line.ends_with('\t'))
+        {
+            trailing += 1;
+        }
+        if !content.is_empty() && !content.ends_with('\n') {
+            no_final_newline += 1;
+        }
+    }
+
+    let mut output = String::new();
+    output.push('\n');
+    output.push_str(&heading(
+        3,
+        file_context.config.heading_offset,
+        "Whitespace",
+    ));
+    output.push_str(&format!("- LF line endings: {} file(s)\n", lf));
+    output.push_str(&format!("- CRLF line endings: {} file(s)\n", crlf));
+    output.push_str(&format!("- Mixed line endings: {} file(s)\n", mixed));
+    output.push_str(&format!("- Trailing whitespace: {} file(s)\n", trailing));
+    output.push_str(&format!(
+        "- Missing final newline: {} file(s)\n",
+        no_final_newline
+    ));
+    output
+}
+
+/// Rank files by the age of their last commit. Untracked files are left out.
+fn dump_file_age_summary(file_context: &FileContext) -> String {
+    let mut output = String::new();
+
+    let mut tracked: Vec<&FileEntry> = file_context
+        .file_entries
+        .iter()
+        .filter(|f| f.last_commit_time.is_some())
+        .collect();
+    // Newest first, ties broken by path for stable output
+    tracked.sort_by(|a, b| {
+        b.last_commit_time
+            .cmp(&a.last_commit_time)
+            .then_with(|| a.path.cmp(&b.path))
+    });
+
+    output.push('\n');
+    output.push_str(&heading(3, file_context.config.heading_offset, "File age"));
+    if tracked.is_empty() {
+        output.push_str("No committed files to rank.\n");
+        return output;
+    }
+
+    output.push_str("Freshest files:\n\n");
+    for f in tracked.iter().take(5) {
+        output.push_str(&format!(
+            "- {}: {}\n",
+            f.path,
+            format_file_age(f, file_context.config.stable)
+        ));
+    }
+
+    output.push_str("\nStalest files:\n\n");
+    for f in tracked.iter().rev().take(5) {
+        output.push_str(&format!(
+            "- {}: {}\n",
+            f.path,
+            format_file_age(f, file_context.config.stable)
+        ));
+    }
+
+    let untracked = file_context.file_entries.len() - tracked.len();
+    if untracked > 0 {
+        output.push_str(&format!("\nUntracked files (no git age): {}\n", untracked));
+    }
+
+    output
+}
+
+/// Human readable age of a file's last commit, e.g. `45 days ago`.
+/// With `stable` the commit date is shown instead, so the text does not change over time.
+fn format_file_age(file: &FileEntry, stable: bool) -> String {
+    match file.last_commit_time {
+        Some(timestamp) if stable => DateTime::from_timestamp(timestamp, 0)
+            .map(|date| date.format("%Y-%m-%d").to_string())
+            .unwrap_or_else(|| "unknown".to_string()),
+        Some(timestamp) => {
+            let days = (current_timestamp() - timestamp).max(0) / 86_400;
+            match days {
+                0 => "today".to_string(),
+                1 => "1 day ago".to_string(),
+                n => format!("{} days ago", n),
+            }
+        }
+        None => "untracked".to_string(),
+    }
+}
+
+/// Current Unix time, or `SOURCE_DATE_EPOCH` when set so builds can be reproduced
+fn current_timestamp() -> i64 {
+    std::env::var("SOURCE_DATE_EPOCH")
+        .ok()
+        .and_then(|epoch| epoch.parse().ok())
+        .unwrap_or_else(|| Utc::now().timestamp())
+}
+
+/// `#` marker for a heading at `level`, shifted down by `offset` and clamped to `######`
+fn heading_marker(level: usize, offset: usize) -> String {
+    "#".repeat((level + offset).min(MAX_HEADING_LEVEL))
+}
+
+/// A markdown heading line followed by a blank line
+fn heading(level: usize, offset: usize, text: &str) -> String {
+    format!("{} {}\n\n", heading_marker(level, offset), text)
+}
+
+/// Deepest heading level markdown supports
+const MAX_HEADING_LEVEL: usize = 6;
+
+fn dump_separator_md() -> String {
+    let mut output = String::new();
+    output.push_str("--------------------------------------------\n\n");
+    output
+}
+
+fn dump_tree_structure(tree_str: &str, offset: usize) -> String {
+    let mut output = String::new();
+
+    // dump tree structure
+    if !tree_str.is_empty() {
+        output.push_str(&heading(2, offset, "Directory Structure"));
+        output.push_str("```\n");
+        output.push_str(tree_str);
+        output.push_str("```\n\n");
+    }
+
+    output.push_str(&dump_separator_md());
+    output
 }
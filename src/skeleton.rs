@@ -0,0 +1,186 @@
+//===----------------------------------------------------------------------===//
+//
+// Copyright (c) 2025 David Rivera
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+//
+// SPDX-License-Identifier: MIT
+//
+//===----------------------------------------------------------------------===//
+//
+// Heuristic "skeleton" rendering: keeps signatures and type declarations of
+// brace-delimited languages while replacing function bodies with `{ ... }`.
+//===----------------------------------------------------------------------===//
+//
+
+/// Extensions of brace-delimited languages the skeleton stripper understands
+const SKELETON_EXTENSIONS: &[&str] = &[
+    "rs", "js", "jsx", "ts", "tsx", "c", "h", "cpp", "cc", "hpp", "java", "kt", "cs", "go",
+    "swift", "php", "scala",
+];
+
+/// Declarations whose bodies are kept, since they hold fields or nested signatures
+const CONTAINER_KEYWORDS: &[&str] = &[
+    "struct",
+    "enum",
+    "union",
+    "impl",
+    "trait",
+    "mod",
+    "class",
+    "interface",
+    "namespace",
+    "extern",
+    "object",
+];
+
+/// Control flow keywords that open a block but are never function signatures
+const CONTROL_KEYWORDS: &[&str] = &[
+    "if", "else", "for", "while", "loop", "match", "switch", "do", "try", "catch", "return",
+];
+
+/// Strip function bodies from `content`, leaving `{ ... }` in their place.
+/// Returns `None` when `ext` is not a supported language, so callers can render it as is.
+pub fn skeletonize(content: &str, ext: &str) -> Option<String> {
+    if !SKELETON_EXTENSIONS.contains(&ext) {
+        return None;
+    }
+
+    let mut output = String::with_capacity(content.len() / 2);
+    // Lines since the last statement boundary, used to classify the block being opened
+    let mut header = String::new();
+    // Brace depth inside the function body currently being skipped
+    let mut skip_depth = 0usize;
+
+    for line in content.lines() {
+        let code = strip_comments_and_strings(line);
+
+        if skip_depth > 0 {
+            for ch in code.chars() {
+                match ch {
+                    '{' => skip_depth += 1,
+                    '}' => skip_depth -= 1,
+                    _ => {}
+                }
+                if skip_depth == 0 {
+                    break;
+                }
+            }
+            continue;
+        }
+
+        header.push_str(&code);
+        header.push(' ');
+
+        match code.find('{') {
+            Some(open) if is_function_header(&header) => {
+                // Keep the signature up to the brace and skip everything that follows
+                let signature = line[..open].trim_end();
+                if signature.trim_start().is_empty() {
+                    // Brace on its own line (e.g. after a `where` clause)
+                    output.push_str(&line[..open]);
+                    output.push_str("{ ... }\n");
+                } else {
+                    output.push_str(signature);
+                    output.push_str(" { ... }\n");
+                }
+                header.clear();
+
+                let mut depth = 0usize;
+                for ch in code[open..].chars() {
+                    match ch {
+                        '{' => depth += 1,
+                        '}' => depth = depth.saturating_sub(1),
+                        _ => {}
+                    }
+                    if depth == 0 {
+                        break;
+                    }
+                }
+                skip_depth = depth;
+            }
+            _ => {
+                output.push_str(line);
+                output.push('\n');
+
+                let trimmed = code.trim_end();
+                if trimmed.is_empty()
+                    || trimmed.ends_with(';')
+                    || trimmed.ends_with('{')
+                    || trimmed.ends_with('}')
+                {
+                    header.clear();
+                }
+            }
+        }
+    }
+
+    Some(output)
+}
+
+/// Whether the text leading up to a `{` looks like a function or method signature
+fn is_function_header(header: &str) -> bool {
+    let before_brace = header.split('{').next().unwrap_or_default();
+    let words: Vec<&str> = before_brace
+        .split(|c: char| !(c.is_alphanumeric() || c == '_'))
+        .filter(|w| !w.is_empty())
+        .collect();
+
+    if words.iter().any(|w| CONTAINER_KEYWORDS.contains(w)) {
+        return false;
+    }
+    if words.first().is_some_and(|w| CONTROL_KEYWORDS.contains(w)) {
+        return false;
+    }
+    // Closures and lambdas passed as arguments are part of a statement, not a signature
+    if before_brace.contains('=') && !before_brace.contains("=>") && !words.contains(&"fn") {
+        return false;
+    }
+
+    words.contains(&"fn") || words.contains(&"func") || words.contains(&"function") || {
+        let trimmed = before_brace.trim_end();
+        trimmed.contains('(') && (trimmed.ends_with(')') || trimmed.contains(") "))
+    }
+}
+
+/// Blank out `//` comments and the contents of double-quoted strings so braces inside
+/// them are ignored. Byte offsets are preserved so indices map back onto the original line.
+fn strip_comments_and_strings(line: &str) -> String {
+    let mut output = String::with_capacity(line.len());
+    let mut chars = line.chars().peekable();
+    let mut in_string = false;
+
+    while let Some(ch) = chars.next() {
+        if in_string {
+            match ch {
+                '\\' => {
+                    output.push(' ');
+                    if let Some(escaped) = chars.next() {
+                        output.push_str(&" ".repeat(escaped.len_utf8()));
+                    }
+                }
+                '"' => {
+                    in_string = false;
+                    output.push('"');
+                }
+                _ => output.push_str(&" ".repeat(ch.len_utf8())),
+            }
+            continue;
+        }
+
+        match ch {
+            '"' => {
+                in_string = true;
+                output.push('"');
+            }
+            '/' if chars.peek() == Some(&'/') => {
+                output.push_str(&" ".repeat(line.len() - output.len()));
+                break;
+            }
+            _ => output.push(ch),
+        }
+    }
+
+    output
+}
@@ -14,26 +14,89 @@
 //===----------------------------------------------------------------------===//
 //
 
-use crate::Config;
-use globset::{Glob, GlobSetBuilder};
+use crate::utils::{redact_path, redaction_pattern};
+use crate::{Config, FileContext, FileEntry, PathStyle};
+use ptree::item::StringItem;
+use ptree::print_config::{PrintConfig, ASCII_CHARS_TICK};
 use ptree::TreeBuilder;
-use std::fs;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::path::{Path, PathBuf};
-use std::time::{Duration, SystemTime};
 
-/// Check if a file was modified within the last 7 days
-fn is_recently_modified(path: &Path) -> Result<bool, Box<dyn std::error::Error>> {
-    let metadata = fs::metadata(path)?;
-    let modified_time = metadata.modified()?;
-    let now = SystemTime::now();
-    let seven_days_ago = now - Duration::from_secs(7 * 24 * 60 * 60);
+/// Structured form of the directory tree, e.g. for JSON export
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TreeNode {
+    pub name: String,
+    pub children: Vec<TreeNode>,
+}
+
+impl From<&StringItem> for TreeNode {
+    fn from(item: &StringItem) -> Self {
+        Self {
+            name: item.text.clone(),
+            children: item.children.iter().map(TreeNode::from).collect(),
+        }
+    }
+}
 
-    Ok(modified_time >= seven_days_ago)
+/// Redact every node name of the tree, each name being a single path segment (or the root label)
+fn redact_tree(item: &mut StringItem, pattern: &Regex) {
+    item.text = redact_path(&item.text, pattern);
+    for child in &mut item.children {
+        redact_tree(child, pattern);
+    }
+}
+
+/// Directory of a tree assembled from already discovered file paths
+#[derive(Debug, Default)]
+struct PathNode {
+    // Entries by name, in name order like a sorted directory listing; files have no node
+    children: BTreeMap<String, Option<PathNode>>,
+}
+
+impl PathNode {
+    fn insert(&mut self, relative: &Path) {
+        let mut node = self;
+        let mut names = relative
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy().to_string())
+            .peekable();
+        while let Some(name) = names.next() {
+            if names.peek().is_none() {
+                _ = node.children.entry(name).or_insert(None);
+                return;
+            }
+            node = node
+                .children
+                .entry(name)
+                .or_insert_with(|| Some(PathNode::default()))
+                .get_or_insert_with(PathNode::default);
+        }
+    }
+}
+
+/// `path` relative to the tree root, trying the root as given, then both paths canonicalized
+fn relative_to_root(path: &Path, root: &Path, canonical_root: Option<&Path>) -> Option<PathBuf> {
+    if let Ok(relative) = path.strip_prefix(root) {
+        return Some(relative.to_path_buf());
+    }
+    let canonical_root = canonical_root?;
+    if let Ok(relative) = path.strip_prefix(canonical_root) {
+        return Some(relative.to_path_buf());
+    }
+    let canonical = path.canonicalize().ok()?;
+    canonical
+        .strip_prefix(canonical_root)
+        .ok()
+        .map(Path::to_path_buf)
 }
 
 #[derive(Debug, Clone)]
 pub struct TreeContext {
     pub tree_str: String,
+    /// Structured tree, available once one of the build methods has run
+    pub tree: Option<TreeNode>,
     config: Config,
 }
 
@@ -41,332 +104,145 @@ impl TreeContext {
     pub fn new(config: Config) -> Self {
         Self {
             tree_str: String::new(),
+            tree: None,
             config,
         }
     }
 
-    /// Build a complete tree hierarchy from the root directory
-    /// Takes into account include/exclude patterns from config
-    pub fn build_tree_from_root(&mut self) -> Result<&mut Self, Box<dyn std::error::Error>> {
-        let root_path = Path::new(&self.config.root_path);
-
-        // Build globsets for filtering
-        let exclude_set = if self.config.exclude_patterns.is_empty() {
-            None
-        } else {
-            Some(self.build_globset(&self.config.exclude_patterns)?)
-        };
-
-        let include_set = if self.config.include_patterns.is_empty() {
-            None
-        } else {
-            Some(self.build_globset(&self.config.include_patterns)?)
-        };
-
-        // Create tree builder
-        let mut tree_builder = TreeBuilder::new(
-            root_path
-                .file_name()
-                .unwrap_or_else(|| std::ffi::OsStr::new("root"))
-                .to_string_lossy()
-                .to_string(),
-        );
+    /// Render the built tree to `tree_str` and keep its structured form
+    fn finish_tree(&mut self, mut tree: StringItem) -> Result<(), Box<dyn std::error::Error>> {
+        if let Some(pattern) = &self.config.redact_path {
+            redact_tree(&mut tree, &redaction_pattern(pattern)?);
+        }
 
-        // Build the tree recursively
-        self.build_tree_recursive(
-            root_path,
-            root_path,
-            &mut tree_builder,
-            &exclude_set,
-            &include_set,
-        )?;
+        let mut print_config = PrintConfig::default();
+        if self.config.ascii_tree {
+            // One column wider so branches read `|-- ` like `tree --charset=ascii`
+            print_config.characters = ASCII_CHARS_TICK.into();
+            print_config.indent = 4;
+        }
 
-        let tree = tree_builder.build();
         let mut buffer = Vec::new();
-        ptree::write_tree_with(&tree, &mut buffer, &ptree::PrintConfig::default())
+        ptree::write_tree_with(&tree, &mut buffer, &print_config)
             .map_err(|e| format!("Failed to write tree: {}", e))?;
         self.tree_str = String::from_utf8(buffer)
             .map_err(|e| format!("Failed to convert tree to string: {}", e))?;
-
-        Ok(self)
+        self.tree = Some(TreeNode::from(&tree));
+        Ok(())
     }
 
-    /// Build a tree hierarchy that only includes paths leading to target files/directories
-    /// Creates a minimal tree showing only relevant paths
-    pub fn build_tree_from_targets(&mut self) -> Result<&mut Self, Box<dyn std::error::Error>> {
+    /// Build the tree from files that were already discovered, without walking the filesystem
+    /// again. The tree then lists exactly the packaged files (and the directories leading to
+    /// them); files outside the root directory are left out. `Config::max_depth` and
+    /// `Config::tree_max_children` limit the depth and breadth of the tree.
+    pub fn build_tree_from_entries(
+        &mut self,
+        entries: &[FileEntry],
+    ) -> Result<&mut Self, Box<dyn std::error::Error>> {
         let root_path = Path::new(&self.config.root_path);
+        let canonical_root = root_path.canonicalize().ok();
 
-        // If no targets specified, build full tree
-        if self.config.target_paths.is_empty() {
-            return self.build_tree_from_root();
-        }
-
-        // FIXME: This is a bit hardcoded IMO, we'll revise this later
-        // Special case: if target is "." or the root path itself, build full tree
-        for target in &self.config.target_paths {
-            let target_path = if Path::new(target).is_absolute() {
-                PathBuf::from(target)
-            } else {
-                root_path.join(target)
-            };
-
-            if let (Ok(canonical_target), Ok(canonical_root)) =
-                (target_path.canonicalize(), root_path.canonicalize())
+        let mut root = PathNode::default();
+        for entry in entries {
+            if let Some(relative) =
+                relative_to_root(&entry.source_path, root_path, canonical_root.as_deref())
             {
-                if canonical_target == canonical_root {
-                    // Target is the root directory, build full tree instead
-                    return self.build_tree_from_root();
-                }
+                root.insert(&relative);
             }
         }
 
-        // Collect all target paths and their parent directories
-        let mut tree_paths = std::collections::HashSet::new();
-        let mut target_directories = std::collections::HashSet::new();
+        let mut tree_builder = TreeBuilder::new(self.root_label(root_path));
+        self.add_path_node(&root, 0, &mut tree_builder);
+        self.finish_tree(tree_builder.build())?;
 
-        for target in &self.config.target_paths {
-            let target_path = if Path::new(target).is_absolute() {
-                PathBuf::from(target)
-            } else {
-                root_path.join(target)
-            };
+        Ok(self)
+    }
 
-            // Add the target path and all its parent directories
-            if let Ok(canonical_target) = target_path.canonicalize() {
-                if let Ok(canonical_root) = root_path.canonicalize() {
-                    if canonical_target.starts_with(&canonical_root) {
-                        // If target is a directory, we'll want to show all its contents
-                        if canonical_target.is_dir() {
-                            _ = target_directories.insert(canonical_target.clone());
-                        }
+    /// Add the children of `node` to the tree, collapsing directories below the depth limit
+    /// and trimming long listings. `depth` is the level of `node`, 0 being the root.
+    fn add_path_node(&self, node: &PathNode, depth: usize, tree_builder: &mut TreeBuilder) {
+        let mut children: Vec<(&String, &Option<PathNode>)> = node.children.iter().collect();
+
+        let mut hidden = Vec::new();
+        if let Some(max_children) = self.config.tree_max_children {
+            if children.len() > max_children {
+                // Subdirectories are kept ahead of files when trimming
+                children.sort_by_key(|(_, child)| child.is_none());
+                hidden = children.split_off(max_children);
+            }
+        }
 
-                        let mut current = canonical_target.as_path();
-                        while current != canonical_root {
-                            _ = tree_paths.insert(current.to_path_buf());
-                            if let Some(parent) = current.parent() {
-                                current = parent;
-                            } else {
-                                break;
-                            }
+        for (name, child) in children {
+            match child {
+                Some(dir) => {
+                    _ = tree_builder.begin_child(name.clone());
+                    if self.config.max_depth.is_some_and(|max| depth + 1 >= max) {
+                        if !dir.children.is_empty() {
+                            _ = tree_builder
+                                .add_empty_child(format!("... ({} more) ...", dir.children.len()));
                         }
-                        _ = tree_paths.insert(canonical_root.clone());
+                    } else {
+                        self.add_path_node(dir, depth + 1, tree_builder);
                     }
+                    _ = tree_builder.end_child();
+                }
+                None => {
+                    _ = tree_builder.add_empty_child(name.clone());
                 }
             }
         }
 
-        // Create tree builder
-        let mut tree_builder = TreeBuilder::new(
-            root_path
-                .file_name()
-                .unwrap_or_else(|| std::ffi::OsStr::new("root"))
-                .to_string_lossy()
-                .to_string(),
-        );
-
-        // Build the tree with only target paths
-        self.build_tree_from_target_paths(
-            root_path,
-            root_path,
-            &mut tree_builder,
-            &tree_paths,
-            &target_directories,
-        )?;
-
-        let tree = tree_builder.build();
-        let mut buffer = Vec::new();
-        ptree::write_tree_with(&tree, &mut buffer, &ptree::PrintConfig::default())
-            .map_err(|e| format!("Failed to write tree: {}", e))?;
-        self.tree_str = String::from_utf8(buffer)
-            .map_err(|e| format!("Failed to convert tree to string: {}", e))?;
-        Ok(self)
-    }
-
-    /// Helper method to build globset from patterns
-    fn build_globset(
-        &self,
-        patterns: &[String],
-    ) -> Result<globset::GlobSet, Box<dyn std::error::Error>> {
-        let mut builder = GlobSetBuilder::new();
-
-        for pattern in patterns {
-            let glob = Glob::new(pattern)?;
-            _ = builder.add(glob);
+        if !hidden.is_empty() {
+            let kind = if hidden.iter().all(|(_, child)| child.is_none()) {
+                "files"
+            } else {
+                "entries"
+            };
+            _ = tree_builder.add_empty_child(format!("... ({} more {}) ...", hidden.len(), kind));
         }
-
-        Ok(builder.build()?)
     }
 
-    /// Check if a path should be included based on include/exclude patterns
-    fn should_include_path(
-        &self,
-        path: &Path,
-        root_path: &Path,
-        exclude_set: &Option<globset::GlobSet>,
-        include_set: &Option<globset::GlobSet>,
-        is_file: bool,
-    ) -> bool {
-        // Get relative path for pattern matching
-        let relative_path = if let Ok(rel_path) = path.strip_prefix(root_path) {
-            rel_path
-        } else {
-            path
-        };
-
-        let path_str = relative_path.to_string_lossy().to_string();
-
-        // Check exclude patterns first
-        if let Some(exclude) = exclude_set {
-            if exclude.is_match(&path_str) {
-                return false;
-            }
-        }
-
-        // For directories, always include if no specific exclude rule matched
-        // This allows traversal into directories that might contain matching files
-        if !is_file {
-            return true;
-        }
-
-        // For files, check include patterns if they exist
-        if let Some(include) = include_set {
-            include.is_match(&path_str)
-        } else {
-            true // Include everything if no include patterns specified
-        }
+    /// Discover the files under the root directory, as packaging would, and build the tree
+    /// from them. Takes into account include/exclude patterns and the other file filters
+    /// from config.
+    pub fn build_tree_from_root(&mut self) -> Result<&mut Self, Box<dyn std::error::Error>> {
+        let files = FileContext::from_root(self.discovery_config(), &self.config.root_path)?;
+        self.build_tree_from_entries(&files.file_entries)
     }
 
-    /// Recursively build tree from root directory
-    fn build_tree_recursive(
-        &self,
-        current_path: &Path,
-        root_path: &Path,
-        tree_builder: &mut TreeBuilder,
-        exclude_set: &Option<globset::GlobSet>,
-        include_set: &Option<globset::GlobSet>,
-    ) -> Result<(), Box<dyn std::error::Error>> {
-        if !current_path.is_dir() {
-            return Ok(());
+    /// Build a tree of the files discovered from the target files/directories, showing only
+    /// the paths leading to them. Without targets this is the full tree.
+    pub fn build_tree_from_targets(&mut self) -> Result<&mut Self, Box<dyn std::error::Error>> {
+        if self.config.target_paths.is_empty() {
+            return self.build_tree_from_root();
         }
+        let files =
+            FileContext::from_target_paths(self.discovery_config(), &self.config.root_path)?;
+        self.build_tree_from_entries(&files.file_entries)
+    }
 
-        let mut entries = fs::read_dir(current_path)?
-            .filter_map(|entry| entry.ok())
-            .collect::<Vec<_>>();
-
-        // Sort entries for consistent output
-        entries.sort_by_key(|a| a.file_name());
-
-        for entry in entries {
-            let entry_path = entry.path();
-            let is_file = entry_path.is_file();
-
-            // Skip if path should be excluded
-            if !self.should_include_path(&entry_path, root_path, exclude_set, include_set, is_file)
-            {
-                continue;
-            }
-
-            let name = entry_path
-                .file_name()
-                .unwrap_or_else(|| std::ffi::OsStr::new("unknown"))
-                .to_string_lossy()
-                .to_string();
-
-            if entry_path.is_dir() {
-                _ = tree_builder.begin_child(name);
-                if self.config.is_recursive {
-                    self.build_tree_recursive(
-                        &entry_path,
-                        root_path,
-                        tree_builder,
-                        exclude_set,
-                        include_set,
-                    )?;
-                }
-                _ = tree_builder.end_child();
-            } else if is_file {
-                // Check recent filter if enabled
-                if self.config.recent_only {
-                    match is_recently_modified(&entry_path) {
-                        Ok(false) => continue, // File is not recent, skip
-                        Err(_) => continue,    // Error checking modification time, skip
-                        Ok(true) => {}         // File is recent, continue processing
-                    }
-                }
-
-                // Only add files that passed the include filter
-                _ = tree_builder.add_empty_child(name);
-            }
+    /// Config for discovering the files of the tree; their content is not needed
+    fn discovery_config(&self) -> Config {
+        Config {
+            dry_run: true,
+            ..self.config.clone()
         }
-
-        Ok(())
     }
 
-    /// Build tree from specific target paths only
-    #[allow(clippy::only_used_in_recursion)]
-    fn build_tree_from_target_paths(
-        &self,
-        current_path: &Path,
-        root_path: &Path,
-        tree_builder: &mut TreeBuilder,
-        target_paths: &std::collections::HashSet<PathBuf>,
-        target_directories: &std::collections::HashSet<PathBuf>,
-    ) -> Result<(), Box<dyn std::error::Error>> {
-        if !current_path.is_dir() {
-            return Ok(());
+    /// Label for the tree root, following the configured path style
+    fn root_label(&self, root_path: &Path) -> String {
+        // The checkout directory name differs between clones
+        if self.config.stable {
+            return ".".to_string();
         }
-
-        let mut entries = fs::read_dir(current_path)?
-            .filter_map(|entry| entry.ok())
-            .filter(|entry| {
-                if let Ok(canonical_path) = entry.path().canonicalize() {
-                    // Include if path is in target_paths OR if current directory is a target directory
-                    target_paths.contains(&canonical_path)
-                        || target_directories
-                            .iter()
-                            .any(|target_dir| canonical_path.starts_with(target_dir))
-                } else {
-                    false
-                }
-            })
-            .collect::<Vec<_>>();
-
-        // Sort entries for consistent output
-        entries.sort_by_key(|a| a.file_name());
-
-        for entry in entries {
-            let entry_path = entry.path();
-
-            let name = entry_path
+        match self.config.path_style {
+            PathStyle::Relative => root_path
                 .file_name()
-                .unwrap_or_else(|| std::ffi::OsStr::new("unknown"))
+                .unwrap_or_else(|| std::ffi::OsStr::new("root"))
                 .to_string_lossy()
-                .to_string();
-
-            if entry_path.is_dir() {
-                _ = tree_builder.begin_child(name);
-                self.build_tree_from_target_paths(
-                    &entry_path,
-                    root_path,
-                    tree_builder,
-                    target_paths,
-                    target_directories,
-                )?;
-                _ = tree_builder.end_child();
-            } else {
-                // Check recent filter if enabled
-                if self.config.recent_only {
-                    match is_recently_modified(&entry_path) {
-                        Ok(false) => continue, // File is not recent, skip
-                        Err(_) => continue,    // Error checking modification time, skip
-                        Ok(true) => {}         // File is recent, continue processing
-                    }
-                }
-
-                _ = tree_builder.add_empty_child(name);
-            }
+                .to_string(),
+            PathStyle::Absolute => root_path.to_string_lossy().to_string(),
+            PathStyle::Repo => ".".to_string(),
         }
-
-        Ok(())
     }
 }
@@ -14,19 +14,313 @@
 //===----------------------------------------------------------------------===//
 //
 
-#[derive(Debug, Clone)]
+use crate::tree::TreeNode;
+use crate::utils::format_timestamp;
+use serde::{Serialize, Serializer};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+/// How file paths are presented in the rendered output
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PathStyle {
+    /// Relative to the root (e.g. `src/main.rs`)
+    #[default]
+    Relative,
+    /// Full filesystem path
+    Absolute,
+    /// Repo-rooted with a leading `./` (e.g. `./src/main.rs`)
+    Repo,
+}
+
+/// Separator used between the line number and the code when line numbers are shown
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LineNumberStyle {
+    /// `12: code`
+    #[default]
+    Colon,
+    /// `12 | code`
+    Pipe,
+    /// `[12] code`
+    Bracket,
+}
+
+impl LineNumberStyle {
+    /// Format a line number right-aligned to `width` columns, followed by the line itself
+    pub fn format_line(&self, number: usize, width: usize, line: &str) -> String {
+        match self {
+            LineNumberStyle::Colon => format!("{:>width$}: {}", number, line),
+            LineNumberStyle::Pipe => format!("{:>width$} | {}", number, line),
+            LineNumberStyle::Bracket => format!("[{:>width$}] {}", number, line),
+        }
+    }
+}
+
+/// How the content of binary files is rendered
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BinaryMode {
+    /// List the file without its content
+    #[default]
+    Skip,
+    /// Embed the bytes as a base64 block
+    Base64,
+    /// Embed the bytes as a hex block
+    Hex,
+}
+
+/// How file blocks are grouped into sections
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GroupBy {
+    /// Inferred role of the file: source, tests, docs, config or build
+    Role,
+}
+
+/// Key the packaged files are ordered by; ties are always broken by path
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SortKey {
+    /// Path, alphabetically
+    #[default]
+    Path,
+    /// File size in bytes
+    Size,
+    /// Number of lines
+    Lines,
+    /// Filesystem modification time, files without one first
+    Modified,
+}
+
+/// What a file is for, inferred from its path (see `utils::file_role`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum FileRole {
+    Source,
+    Tests,
+    Docs,
+    Config,
+    Build,
+}
+
+impl FileRole {
+    /// All roles in the order their sections are rendered
+    pub const ALL: [FileRole; 5] = [
+        FileRole::Source,
+        FileRole::Tests,
+        FileRole::Docs,
+        FileRole::Config,
+        FileRole::Build,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            FileRole::Source => "Source",
+            FileRole::Tests => "Tests",
+            FileRole::Docs => "Docs",
+            FileRole::Config => "Config",
+            FileRole::Build => "Build",
+        }
+    }
+}
+
+/// Discovery progress reported through `Config::progress`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProgressEvent {
+    /// Candidate files found so far while walking the tree
+    Discovered(usize),
+    /// Files read so far, out of `total`
+    Processed { done: usize, total: usize },
+}
+
+/// Callback receiving `ProgressEvent`s, so front ends can show progress without the library
+/// depending on a progress bar crate. It may be called from several threads at once.
+#[derive(Clone)]
+pub struct ProgressHook(Arc<dyn Fn(ProgressEvent) + Send + Sync>);
+
+impl ProgressHook {
+    pub fn new(hook: impl Fn(ProgressEvent) + Send + Sync + 'static) -> Self {
+        Self(Arc::new(hook))
+    }
+
+    pub fn report(&self, event: ProgressEvent) {
+        (self.0)(event)
+    }
+}
+
+impl std::fmt::Debug for ProgressHook {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("ProgressHook")
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
 pub struct Config {
     // Repo root path - now discovered from current working directory
     pub root_path: String,
     // Target paths/files to process
     pub target_paths: Vec<String>,
+    // Discover the enclosing git repository; when unset `root_path` is used as the root and
+    // no git information is collected
+    pub use_git: bool,
+    // Accept target paths that resolve outside the repository root
+    pub allow_outside_root: bool,
     pub output_file: Option<String>,
+    // Copy the output to the system clipboard, takes precedence over `output_file`
+    pub clipboard: bool,
+    // Also print the output to stdout when writing `output_file`
+    pub tee: bool,
     pub include_patterns: Vec<String>,
     pub exclude_patterns: Vec<String>,
     pub is_recursive: bool,
     pub recent_only: bool,
     // Presentation flags
     pub show_line_numbers: bool,
+    // Annotate files with the age of their most recent commit
+    pub show_file_age: bool,
+    // Annotate files with the hash of the last commit that modified them
+    pub git_annotate: bool,
+    // Expand tabs to this many spaces in rendered content (source files are untouched)
+    pub expand_tabs: Option<usize>,
+    // Only expand tabs in leading indentation, leaving tabs inside lines alone
+    pub tabs_leading_only: bool,
+    // Cut rendered lines longer than this many characters (source files are untouched)
+    pub max_line_width: Option<usize>,
+    // Attach the generated output as a git note on HEAD under this notes ref
+    pub git_note_ref: Option<String>,
+    // Render only files changed since this git ref in full, summarizing the rest
+    pub focus_changes: Option<String>,
+    // Treat files whose changes are whitespace-only as unchanged
+    pub dedupe_whitespace_only_diff: bool,
+    // Render byte-identical files only once, later copies point to the first one
+    pub dedupe: bool,
+    // Presentation of file paths in headers and the tree
+    pub path_style: PathStyle,
+    // File listing content hashes; only files whose content matches one are packaged
+    pub only_hashes: Option<String>,
+    // Annotate file headers with their size rank and percentile
+    pub show_size_rank: bool,
+    // Write one output file per extension into this directory instead of a single output
+    pub split_by_ext: Option<String>,
+    // Report line ending and trailing whitespace statistics in the summary
+    pub whitespace_stats: bool,
+    // Omit content of files larger than this multiple of the median file size
+    pub trim_outliers: Option<f64>,
+    // Number of recent commits to show as an ASCII graph in the metadata
+    pub git_graph: Option<usize>,
+    // Number of recent commits to list in the metadata, 0 to leave the list out
+    pub git_log: usize,
+    // List uncommitted changes of the working tree and index in the metadata
+    pub git_status: bool,
+    // Write the structured directory tree as JSON to this file
+    pub tree_json: Option<String>,
+    // Stop embedding file content once this many lines have been rendered in total
+    pub max_total_render_lines: Option<usize>,
+    // Only package files (smallest first) while their estimated tokens fit in this budget
+    pub max_tokens: Option<u64>,
+    // Regex of path segments to replace with `***` in rendered paths and the tree
+    pub redact_path: Option<String>,
+    // Mask likely secrets (AWS keys, private keys, token assignments) in file content
+    pub redact: bool,
+    // List the dependencies of a root Cargo.toml in the summary
+    pub deps: bool,
+    // Package only files with staged changes, using their content from the index
+    pub staged: bool,
+    // Package the changes between two refs, given as `base..head`
+    pub range: Option<String>,
+    // Omit the content of large JSON/YAML/CSV files
+    pub skip_data: bool,
+    // Print the size of the generated output to stderr
+    pub report_output_size: bool,
+    // Exclude paths containing any of these plain substrings
+    pub exclude_contains: Vec<String>,
+    // Only include files with one of these extensions (case-insensitive, no leading dot)
+    pub include_exts: Vec<String>,
+    // Exclude files with any of these extensions (case-insensitive, no leading dot)
+    pub exclude_exts: Vec<String>,
+    // Start the output with a generated overview paragraph
+    pub overview: bool,
+    // Separator style for line numbers
+    pub line_number_style: LineNumberStyle,
+    // How binary files below the content size limit are rendered
+    pub binary_mode: BinaryMode,
+    // Only package files last modified by this author (name or email)
+    pub author: Option<String>,
+    // Only package files changed between this git revision and HEAD
+    pub changed_since: Option<String>,
+    // Append a short fingerprint of the packaged files to the output file name
+    pub content_addressed: bool,
+    // Gzip the output file, appending `.gz` to its name
+    pub gzip: bool,
+    // List TODO/FIXME style markers in the summary
+    pub todos: bool,
+    // Markers searched by `todos`, empty for the defaults
+    pub todo_markers: Vec<String>,
+    // Group files by directory and close each group with a mini-summary
+    pub dir_summaries: bool,
+    // Group files into sections by an inferred property, takes precedence over `dir_summaries`
+    pub group_by: Option<GroupBy>,
+    // Order of the file blocks in the output
+    pub sort: SortKey,
+    // Reverse `sort` into descending order
+    pub sort_descending: bool,
+    // Only package files of the dominant language (plus Markdown)
+    pub primary_lang_only: bool,
+    // Render only signatures and type declarations of brace-delimited code
+    pub skeleton: bool,
+    // Shift every generated markdown heading down by this many levels
+    pub heading_offset: usize,
+    // Recurse into submodule working directories instead of skipping them
+    pub include_submodules: bool,
+    // Descend into symlinked directories, each real directory at most once
+    pub follow_symlinks: bool,
+    // Only load content for files smaller than this many bytes, None for the 1MB default
+    pub max_content_bytes: Option<u64>,
+    // Leave files larger than this many bytes out of discovery entirely
+    pub skip_larger_than: Option<u64>,
+    // Stop discovery once this many files have been collected
+    pub max_files: Option<usize>,
+    // How far back `recent_only` looks, None for the default 7 days
+    pub recent_window: Option<Duration>,
+    // File listing the exact paths to package, one per line
+    pub files_from: Option<String>,
+    // Skip (with a warning) listed paths that do not exist instead of failing
+    pub ignore_missing: bool,
+    // Truncate the generated output (with a trailing notice) at this many bytes
+    pub max_output_bytes: Option<u64>,
+    // Maximum number of children shown per directory in the tree
+    pub tree_max_children: Option<usize>,
+    // Extensions listed in the language breakdown before the rest are rolled up, 0 for all
+    pub lang_breakdown_limit: usize,
+    // Deepest directory level expanded in the tree (file discovery is unaffected)
+    pub max_depth: Option<usize>,
+    // Draw the tree with ASCII connectors (`|--`, `` `-- ``) instead of box-drawing characters
+    pub ascii_tree: bool,
+    // Diff-friendly output: no absolute paths, modification times or relative dates, LF
+    // line endings
+    pub stable: bool,
+    // Include an estimated human reading time in the summary
+    pub reading_time: bool,
+    // Score files by branch keyword count and rank the most complex ones
+    pub complexity: bool,
+    // Drop empty and whitespace-only files from the output and the tree
+    pub exclude_empty: bool,
+    // Drop text files with fewer lines than this (binary files are kept)
+    pub min_lines: Option<u64>,
+    // Include dotfiles and dot-directories (the `.git` directory is always skipped)
+    pub include_hidden: bool,
+    // Read discovered files on one thread even when built with the `parallel` feature
+    pub single_threaded: bool,
+    // Only list the files that would be packaged, without reading their content
+    pub dry_run: bool,
+    // Render file headers, sizes and line counts but no file content
+    pub metadata_only: bool,
+    // Receives discovery progress, e.g. to drive the CLI's --progress bar
+    #[serde(skip)]
+    pub progress: Option<ProgressHook>,
+    // Prepend YAML front matter with file metadata to each file section
+    pub front_matter: bool,
 }
 
 impl Default for Config {
@@ -34,26 +328,177 @@ impl Default for Config {
         Self {
             root_path: "".to_string(),
             target_paths: Vec::new(),
+            use_git: true,
+            allow_outside_root: false,
             output_file: None,
+            clipboard: false,
+            tee: false,
             include_patterns: Vec::new(),
             exclude_patterns: Vec::new(),
             is_recursive: true,
             show_line_numbers: false,
             recent_only: false,
+            show_file_age: false,
+            git_annotate: false,
+            expand_tabs: None,
+            max_line_width: None,
+            tabs_leading_only: false,
+            git_note_ref: None,
+            focus_changes: None,
+            dedupe_whitespace_only_diff: false,
+            dedupe: false,
+            path_style: PathStyle::default(),
+            only_hashes: None,
+            show_size_rank: false,
+            split_by_ext: None,
+            whitespace_stats: false,
+            trim_outliers: None,
+            git_graph: None,
+            git_log: 0,
+            git_status: false,
+            tree_json: None,
+            max_total_render_lines: None,
+            max_tokens: None,
+            redact_path: None,
+            redact: false,
+            deps: false,
+            staged: false,
+            range: None,
+            skip_data: false,
+            report_output_size: false,
+            exclude_contains: Vec::new(),
+            include_exts: Vec::new(),
+            exclude_exts: Vec::new(),
+            overview: false,
+            line_number_style: LineNumberStyle::default(),
+            binary_mode: BinaryMode::default(),
+            author: None,
+            changed_since: None,
+            content_addressed: false,
+            gzip: false,
+            todos: false,
+            todo_markers: Vec::new(),
+            dir_summaries: false,
+            group_by: None,
+            sort: SortKey::default(),
+            sort_descending: false,
+            primary_lang_only: false,
+            skeleton: false,
+            heading_offset: 0,
+            include_submodules: false,
+            follow_symlinks: false,
+            max_content_bytes: None,
+            skip_larger_than: None,
+            max_files: None,
+            recent_window: None,
+            files_from: None,
+            ignore_missing: false,
+            max_output_bytes: None,
+            tree_max_children: None,
+            lang_breakdown_limit: 10,
+            max_depth: None,
+            ascii_tree: false,
+            stable: false,
+            reading_time: false,
+            complexity: false,
+            exclude_empty: false,
+            min_lines: None,
+            include_hidden: false,
+            single_threaded: false,
+            dry_run: false,
+            metadata_only: false,
+            progress: None,
+            front_matter: false,
         }
     }
 }
 
-#[derive(Debug, Clone)]
+/// Window used by `recent_only` when no `recent_window` is configured
+pub const DEFAULT_RECENT_WINDOW: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+impl Config {
+    /// Modification window applied by `recent_only`
+    pub fn recent_window(&self) -> Duration {
+        self.recent_window.unwrap_or(DEFAULT_RECENT_WINDOW)
+    }
+
+    /// Whether discovery has to load file content. A dry run or a metadata-only run skips it
+    /// unless a filter depends on the content or its hash.
+    pub fn loads_content(&self) -> bool {
+        !(self.dry_run || self.metadata_only) || self.only_hashes.is_some() || self.exclude_empty
+    }
+}
+
+/// Line terminator style of a text file
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LineEnding {
+    /// `\n` only, also used for files without any line break
+    #[default]
+    Lf,
+    /// `\r\n` only
+    CrLf,
+    /// Both `\n` and `\r\n`
+    Mixed,
+}
+
+impl LineEnding {
+    /// Classify the line breaks found in `content`
+    pub fn detect(content: &str) -> Self {
+        let total = content.matches('\n').count();
+        let crlf = content.matches("\r\n").count();
+        match crlf {
+            0 => LineEnding::Lf,
+            n if n == total => LineEnding::CrLf,
+            _ => LineEnding::Mixed,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
 pub struct FileEntry {
     pub path: String,
     pub content: Option<String>, // None for binary files
     pub size: u64,               // In bytes
     pub lines: u64,              // Number of lines
+    // Line terminators used by the content, Lf for binary files
+    pub line_ending: LineEnding,
+    // Estimated LLM token count, 0 for binary files
+    pub tokens: u64,
     pub is_binary: bool,
+    // Unix timestamp of the last commit touching this file, None if untracked or not computed
+    pub last_commit_time: Option<i64>,
+    // Hash of the last commit touching this file, only computed with `--git-annotate`
+    pub last_commit: Option<String>,
+    // Filesystem modification time, serialized as an ISO-8601 string
+    #[serde(serialize_with = "serialize_timestamp")]
+    pub modified: Option<SystemTime>,
+    // Where discovery found the file, as walked (used to place it in the tree)
+    #[serde(skip)]
+    pub source_path: PathBuf,
+    // First and last line (1-based) kept from a `path:start-end` target, None for whole files
+    pub line_range: Option<(u64, u64)>,
+    // Whether the file differs from the compared git ref, None if not computed
+    pub changed: Option<bool>,
+    // Hex SHA-256 of the file bytes, None when content was not read
+    pub content_hash: Option<String>,
+    // Content contained invalid UTF-8 (or UTF-16) and was decoded lossily
+    pub content_lossy: bool,
+    // Bytes of a binary file, only kept when binary files are embedded (see `BinaryMode`)
+    #[serde(skip)]
+    pub raw_bytes: Option<Vec<u8>>,
+    // Why the content was deliberately left out, if it was
+    pub omitted_reason: Option<String>,
 }
 
-#[derive(Debug, Clone)]
+fn serialize_timestamp<S: Serializer>(
+    time: &Option<SystemTime>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    time.map(format_timestamp).serialize(serializer)
+}
+
+#[derive(Debug, Clone, Default)]
 pub struct GitInfo {
     pub is_repo: bool,
     // FIXME: We may not need Option<> for all these fields, as we default to "unknown" or similar
@@ -62,21 +507,117 @@ pub struct GitInfo {
     pub author: Option<String>,
     pub email: Option<String>,
     pub date: Option<String>,
+    // URL of the `origin` remote, None when there is none
+    pub remote_url: Option<String>,
+    // `git describe --tags` of HEAD, or the short commit hash when no tag is reachable
+    pub describe: Option<String>,
+    // Pre-rendered ASCII graph lines of recent history, newest first
+    pub commit_graph: Vec<String>,
+    // Last commits reachable from HEAD, newest first, only collected with `--git-log`
+    pub recent_commits: Vec<CommitSummary>,
+    // Pull-request-style view of `base..head`, when requested
+    pub range: Option<RangeDiff>,
+    // Submodules registered in the repository
+    pub submodules: Vec<SubmoduleInfo>,
+    // Uncommitted changes sorted by path, only collected with `--git-status`
+    pub status: Vec<(String, ChangeKind)>,
+}
+
+/// One commit of the recent history listed by `--git-log`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CommitSummary {
+    pub hash: String,
+    pub author: String,
+    // Commit date as YYYY-MM-DD
+    pub date: String,
+    // First line of the commit message
+    pub message: String,
 }
 
-#[derive(Debug, Clone)]
+/// How an uncommitted path differs from HEAD
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChangeKind {
+    Modified,
+    Added,
+    Deleted,
+    Renamed,
+    Untracked,
+}
+
+impl ChangeKind {
+    pub fn label(&self) -> &'static str {
+        match self {
+            ChangeKind::Modified => "modified",
+            ChangeKind::Added => "added",
+            ChangeKind::Deleted => "deleted",
+            ChangeKind::Renamed => "renamed",
+            ChangeKind::Untracked => "untracked",
+        }
+    }
+}
+
+/// A git submodule as recorded in `.gitmodules` and the HEAD tree
+#[derive(Debug, Clone, Default)]
+pub struct SubmoduleInfo {
+    pub name: String,
+    pub path: String,
+    pub url: Option<String>,
+    // Commit pinned in HEAD, None when the submodule is not committed yet
+    pub commit: Option<String>,
+}
+
+/// Files and commits between two refs, as packaged by `--range base..head`
+#[derive(Debug, Clone, Default)]
+pub struct RangeDiff {
+    pub base: String,
+    pub head: String,
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    pub modified: Vec<String>,
+    // One `<short hash> <summary>` line per commit, newest first
+    pub commits: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default)]
 pub struct RepositoryContext {
     pub root_path: String,
     pub git_info: GitInfo,
     pub file_ctx: FileContext,
     // Stores the tree representation as a string for easy output
     pub tree_repr: String,
+    // Structured tree, None when the context was assembled without building one
+    pub tree: Option<TreeNode>,
+    // Dependencies of the root Cargo.toml, only read with `--deps`
+    pub dependencies: Vec<Dependency>,
+}
+
+/// A crate listed in `[dependencies]` or `[dev-dependencies]`
+#[derive(Debug, Clone, Default)]
+pub struct Dependency {
+    pub name: String,
+    // Version requirement, or where the crate comes from when there is none
+    pub requirement: String,
+    pub dev: bool,
+}
+
+/// Files found by `FileContext::discover_files` under one directory
+#[derive(Debug, Clone, Default)]
+pub struct Discovery {
+    pub entries: Vec<FileEntry>,
+    // Files left out by `Config::skip_larger_than`
+    pub skipped_by_size: usize,
+    // Files that could not be read, with the reason
+    pub skipped: Vec<(String, String)>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct FileContext {
     pub file_entries: Vec<FileEntry>,
-    pub config: Config, // pub tree: Vec<TreeEntry>, TODO
+    // Files left out by `Config::skip_larger_than`
+    pub skipped_by_size: usize,
+    // Secrets masked by `Config::redact`
+    pub redactions: usize,
+    // Files that could not be read, with the reason
+    pub skipped: Vec<(String, String)>,
+    pub config: Config,
 }
-
-// TODO: If we want to represent errors clearly with the user, we should define custom error types here
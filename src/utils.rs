@@ -0,0 +1,364 @@
+//===----------------------------------------------------------------------===//
+//
+// Copyright (c) 2025 David Rivera
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+//
+// SPDX-License-Identifier: MIT
+//
+//===----------------------------------------------------------------------===//
+//
+// Small helpers shared across modules.
+//===----------------------------------------------------------------------===//
+//
+
+use crate::types::FileRole;
+use chrono::{DateTime, SecondsFormat, Utc};
+use regex::{Captures, Regex};
+use std::sync::OnceLock;
+use std::time::{Duration, SystemTime};
+
+/// Normalize a relative path so paths coming from git and from the filesystem walk
+/// compare equal: forward slashes as separators and no leading `./`.
+pub fn normalize_path(path: &str) -> String {
+    let mut normalized = path.replace('\\', "/");
+    while let Some(stripped) = normalized.strip_prefix("./") {
+        normalized = stripped.to_string();
+    }
+    normalized
+}
+
+/// Parse a human readable size such as `512`, `300KB`, `2MB` or `1.5GiB` into bytes.
+/// `K`/`KB`, `M`/`MB` and `G`/`GB` are decimal; `KiB`, `MiB` and `GiB` are binary.
+pub fn parse_size(input: &str) -> Result<u64, String> {
+    let input = input.trim();
+    let split = input
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(input.len());
+    let (number, unit) = input.split_at(split);
+
+    let number: f64 = number
+        .parse()
+        .map_err(|_| format!("Invalid size '{}': expected a number like 2MB", input))?;
+    let multiplier: u64 = match unit.trim().to_ascii_lowercase().as_str() {
+        "" | "b" => 1,
+        "k" | "kb" => 1_000,
+        "m" | "mb" => 1_000_000,
+        "g" | "gb" => 1_000_000_000,
+        "kib" => 1 << 10,
+        "mib" => 1 << 20,
+        "gib" => 1 << 30,
+        other => return Err(format!("Invalid size unit '{}' in '{}'", other, input)),
+    };
+
+    Ok((number * multiplier as f64).round() as u64)
+}
+
+/// Parse a duration such as `30d`, `12h` or `45m` (days, hours, minutes)
+pub fn parse_duration(input: &str) -> Result<Duration, String> {
+    let input = input.trim();
+    let (seconds_per_unit, number): (u64, _) = match input.chars().last() {
+        Some('d') => (24 * 60 * 60, &input[..input.len() - 1]),
+        Some('h') => (60 * 60, &input[..input.len() - 1]),
+        Some('m') => (60, &input[..input.len() - 1]),
+        _ => {
+            return Err(format!(
+                "Invalid duration '{}': expected a number with a d, h or m suffix (e.g. 30d)",
+                input
+            ))
+        }
+    };
+    let number: u64 = number.parse().map_err(|_| {
+        format!(
+            "Invalid duration '{}': expected e.g. 30d, 12h or 45m",
+            input
+        )
+    })?;
+    let seconds = number
+        .checked_mul(seconds_per_unit)
+        .ok_or_else(|| format!("Invalid duration '{}': too large", input))?;
+
+    Ok(Duration::from_secs(seconds))
+}
+
+/// ISO-8601 (RFC 3339) UTC rendering of `time` with second precision, e.g.
+/// `2025-06-01T12:30:00Z`
+pub fn format_timestamp(time: SystemTime) -> String {
+    DateTime::<Utc>::from(time).to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
+/// Average number of characters per token used by `estimate_tokens`
+const CHARS_PER_TOKEN: u64 = 4;
+
+/// Number of lines in `text`, as rendered with line numbers: a final line without a trailing
+/// newline counts, an empty remainder after the last newline does not
+pub fn count_lines(text: &str) -> u64 {
+    text.lines().count() as u64
+}
+
+/// Estimate how many LLM tokens `text` takes, using the common rule of thumb of roughly
+/// four characters per token for English text and source code
+pub fn estimate_tokens(text: &str) -> u64 {
+    (text.chars().count() as u64).div_ceil(CHARS_PER_TOKEN)
+}
+
+/// Token estimate for a text file whose content was not loaded, assuming one byte per character
+pub fn estimate_tokens_from_size(size: u64) -> u64 {
+    size.div_ceil(CHARS_PER_TOKEN)
+}
+
+/// Placeholder shown instead of a redacted path segment
+pub const REDACTED_SEGMENT: &str = "***";
+
+/// Compile a `--redact-path` pattern
+pub fn redaction_pattern(pattern: &str) -> Result<Regex, String> {
+    Regex::new(pattern).map_err(|e| format!("Invalid --redact-path pattern '{}': {}", pattern, e))
+}
+
+/// Replace every `/` separated segment of `path` that matches `pattern` with `***`.
+/// Segments are redacted the same way wherever they appear, so the tree, headers and
+/// listings keep lining up.
+pub fn redact_path(path: &str, pattern: &Regex) -> String {
+    path.split('/')
+        .map(|segment| {
+            if !segment.is_empty() && pattern.is_match(segment) {
+                REDACTED_SEGMENT
+            } else {
+                segment
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Placeholder shown instead of a redacted secret
+pub const REDACTED_SECRET: &str = "[REDACTED]";
+
+/// Patterns of likely secrets. Only the `secret` group is masked, so key names and the
+/// `BEGIN`/`END` lines of a key block stay readable.
+fn secret_patterns() -> &'static [Regex] {
+    static PATTERNS: OnceLock<Vec<Regex>> = OnceLock::new();
+    PATTERNS.get_or_init(|| {
+        [
+            // AWS access key ids
+            r"\b(?P<secret>(?:AKIA|ASIA)[0-9A-Z]{16})\b",
+            // PEM private key bodies
+            r"(?s)-----BEGIN [A-Z ]*PRIVATE KEY-----(?P<secret>.*?)-----END [A-Z ]*PRIVATE KEY-----",
+            // Quoted values assigned to token/secret/password/api key names
+            r#"(?i)[a-z0-9_.-]*(?:token|secret|passw(?:or)?d|api_?key)[a-z0-9_.-]*["']?\s*[:=]\s*["'](?P<secret>[^"'\s]{8,})["']"#,
+            // Unquoted `NAME=value` lines as in .env files and shell scripts
+            r"(?im)^\s*(?:export\s+)?[a-z0-9_]*(?:token|secret|passw(?:or)?d|api_?key)[a-z0-9_]*=(?P<secret>[^\s'#]{8,})\s*$",
+        ]
+        .iter()
+        .map(|pattern| Regex::new(pattern).expect("secret pattern should compile"))
+        .collect()
+    })
+}
+
+/// Replace likely secrets in `content` with `[REDACTED]`, returning the masked text and
+/// the number of replacements
+pub fn redact_content(content: &str) -> (String, usize) {
+    let mut redacted = content.to_string();
+    let mut count = 0;
+
+    for pattern in secret_patterns() {
+        let masked = pattern.replace_all(&redacted, |caps: &Captures| {
+            let whole = caps.get(0).expect("group 0 is always present");
+            let secret = caps
+                .name("secret")
+                .expect("every pattern has a secret group");
+            count += 1;
+            // Keep the line breaks of a key block around the placeholder
+            let placeholder = if secret.as_str().starts_with('\n') {
+                format!("\n{}\n", REDACTED_SECRET)
+            } else {
+                REDACTED_SECRET.to_string()
+            };
+            format!(
+                "{}{}{}",
+                &whole.as_str()[..secret.start() - whole.start()],
+                placeholder,
+                &whole.as_str()[secret.end() - whole.start()..]
+            )
+        });
+        redacted = masked.into_owned();
+    }
+
+    (redacted, count)
+}
+
+/// File names that drive the build
+const BUILD_FILES: &[&str] = &[
+    "build.rs",
+    "makefile",
+    "cmakelists.txt",
+    "dockerfile",
+    "justfile",
+    "build.gradle",
+    "build.gradle.kts",
+    "pom.xml",
+    "meson.build",
+];
+
+/// Extensions of documentation files
+const DOC_EXTENSIONS: &[&str] = &["md", "markdown", "rst", "adoc", "txt"];
+
+/// Extensions and file names of configuration files
+const CONFIG_EXTENSIONS: &[&str] = &["toml", "yaml", "yml", "json", "ini", "cfg", "conf", "lock"];
+const CONFIG_FILES: &[&str] = &[".gitignore", ".gitattributes", ".editorconfig", ".env"];
+
+/// Infer a file's role from its path. Anything under or named like a test counts as a test,
+/// then build files, docs and config are recognized by name or extension; the rest is source.
+pub fn file_role(path: &str) -> FileRole {
+    let path = normalize_path(path);
+    let is_test = is_test_path(&path);
+    let path = path.to_lowercase();
+    let name = path.rsplit('/').next().unwrap_or_default();
+    let ext = extension_key(name);
+
+    if is_test {
+        FileRole::Tests
+    } else if BUILD_FILES.contains(&name) || ext == "mk" || ext == "cmake" {
+        FileRole::Build
+    } else if DOC_EXTENSIONS.contains(&ext.as_str()) || path.starts_with("docs/") {
+        FileRole::Docs
+    } else if CONFIG_EXTENSIONS.contains(&ext.as_str()) || CONFIG_FILES.contains(&name) {
+        FileRole::Config
+    } else {
+        FileRole::Source
+    }
+}
+
+/// Whether a path segment names a test: `tests/`, `test_utils.py`, `parser_test.go`,
+/// `app.spec.ts` or `ParserTest.java`, but not `latest.rs`
+fn is_test_path(path: &str) -> bool {
+    path.split('/').any(|segment| {
+        let stem = segment.split('.').next().unwrap_or_default();
+        stem.ends_with("Test")
+            || stem.ends_with("Tests")
+            || segment
+                .split(['_', '-', '.'])
+                .any(|word| matches!(word.to_lowercase().as_str(), "test" | "tests" | "spec"))
+    })
+}
+
+/// Whether the (normalized) path contains any of the given plain substrings
+pub fn path_contains_any(path: &str, needles: &[String]) -> bool {
+    let path = normalize_path(path);
+    needles.iter().any(|needle| path.contains(needle.as_str()))
+}
+
+/// Whether the final extension of `path` passes the `include` allowlist (ignored when empty)
+/// and is not in the `exclude` denylist. Case-insensitive; a leading `.` in the lists is allowed.
+pub fn extension_allowed(path: &str, include: &[String], exclude: &[String]) -> bool {
+    let ext = std::path::Path::new(path)
+        .extension()
+        .map(|ext| ext.to_string_lossy())
+        .unwrap_or_default();
+    let listed = |exts: &[String]| {
+        exts.iter()
+            .any(|listed| listed.trim_start_matches('.').eq_ignore_ascii_case(&ext))
+    };
+
+    (include.is_empty() || (!ext.is_empty() && listed(include)))
+        && (ext.is_empty() || !listed(exclude))
+}
+
+/// Lowercase extension used to bucket files by language, empty when there is none
+pub fn extension_key(path: &str) -> String {
+    // Use extension as a proxy for language (simple heuristic)
+    match path.rsplit('.').next() {
+        Some(seg) if seg != path => seg.to_lowercase(),
+        _ => String::from(""),
+    }
+}
+
+/// Known file extensions and the human readable language they map to
+const LANGUAGES: &[(&str, &str)] = &[
+    ("rs", "Rust"),
+    ("py", "Python"),
+    ("js", "JavaScript"),
+    ("jsx", "JavaScript"),
+    ("ts", "TypeScript"),
+    ("tsx", "TypeScript"),
+    ("go", "Go"),
+    ("java", "Java"),
+    ("kt", "Kotlin"),
+    ("c", "C"),
+    ("h", "C"),
+    ("cpp", "C++"),
+    ("cc", "C++"),
+    ("hpp", "C++"),
+    ("cs", "C#"),
+    ("rb", "Ruby"),
+    ("php", "PHP"),
+    ("swift", "Swift"),
+    ("sh", "Shell"),
+    ("md", "Markdown"),
+    ("toml", "TOML"),
+    ("yml", "YAML"),
+    ("yaml", "YAML"),
+    ("json", "JSON"),
+    ("html", "HTML"),
+    ("css", "CSS"),
+];
+
+/// File extensions whose markdown code fence identifier differs from the extension itself.
+/// Extensions not listed here (e.g. `go`, `c`, `json`) are already valid identifiers.
+const FENCE_LANGUAGES: &[(&str, &str)] = &[
+    ("rs", "rust"),
+    ("py", "python"),
+    ("pyi", "python"),
+    ("js", "javascript"),
+    ("mjs", "javascript"),
+    ("cjs", "javascript"),
+    ("ts", "typescript"),
+    ("mts", "typescript"),
+    ("cts", "typescript"),
+    ("rb", "ruby"),
+    ("kt", "kotlin"),
+    ("kts", "kotlin"),
+    ("cs", "csharp"),
+    ("h", "c"),
+    ("cc", "cpp"),
+    ("cxx", "cpp"),
+    ("hh", "cpp"),
+    ("hpp", "cpp"),
+    ("sh", "bash"),
+    ("ps1", "powershell"),
+    ("bat", "batch"),
+    ("cmd", "batch"),
+    ("md", "markdown"),
+    ("yml", "yaml"),
+    ("htm", "html"),
+    ("hs", "haskell"),
+    ("ex", "elixir"),
+    ("exs", "elixir"),
+    ("ml", "ocaml"),
+    ("pl", "perl"),
+    ("jl", "julia"),
+    ("tf", "hcl"),
+];
+
+/// Markdown code fence identifier for a file, e.g. `rust` for `main.rs`. Unknown extensions
+/// are used as is, and files without an extension get an empty identifier.
+pub fn fence_language(path: &str) -> &str {
+    let file_name = path.rsplit(['/', '\\']).next().unwrap_or(path);
+    let Some(dot_pos) = file_name.rfind('.') else {
+        return "";
+    };
+    let ext = &file_name[dot_pos + 1..];
+    FENCE_LANGUAGES
+        .iter()
+        .find(|(known, _)| known.eq_ignore_ascii_case(ext))
+        .map_or(ext, |(_, language)| language)
+}
+
+/// Look up the language name for a lowercase file extension
+pub fn language_name(ext: &str) -> Option<&'static str> {
+    LANGUAGES
+        .iter()
+        .find(|(known, _)| *known == ext)
+        .map(|(_, name)| *name)
+}
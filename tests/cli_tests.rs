@@ -0,0 +1,574 @@
+//===----------------------------------------------------------------------===//
+//
+// Copyright (c) 2025 David Rivera
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+//
+// SPDX-License-Identifier: MIT
+//
+//===----------------------------------------------------------------------===//
+//
+// Tests for command line parsing
+//===----------------------------------------------------------------------===//
+//
+
+use clap::Parser;
+use rusty_repo_context_manager::output::output_size_report;
+use rusty_repo_context_manager::{utils, Cli, Config, FileContext};
+use std::fs;
+use std::io::Write;
+use std::process::{Command, Stdio};
+use std::time::Duration;
+use tempfile::TempDir;
+
+fn parse(args: &[&str]) -> Cli {
+    Cli::try_parse_from(std::iter::once("repo-context").chain(args.iter().copied()))
+        .expect("arguments should parse")
+}
+
+/// Create a committed git repository containing the given files
+fn init_repo(files: &[(&str, &str)]) -> TempDir {
+    let dir = TempDir::new().unwrap();
+    for (path, content) in files {
+        let path = dir.path().join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, content).unwrap();
+    }
+
+    let repo = git2::Repository::init(dir.path()).unwrap();
+    let mut index = repo.index().unwrap();
+    index
+        .add_all(["*"], git2::IndexAddOption::DEFAULT, None)
+        .unwrap();
+    let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+    let sig = git2::Signature::now("Test User", "test@example.com").unwrap();
+    let _ = repo
+        .commit(Some("HEAD"), &sig, &sig, "Initial commit", &tree, &[])
+        .unwrap();
+    dir
+}
+
+/// Run the compiled binary with `dir` as the working directory
+fn run_binary(dir: &TempDir, args: &[&str]) -> std::process::Output {
+    Command::new(env!("CARGO_BIN_EXE_rusty-repo-context-manager"))
+        .current_dir(dir.path())
+        .args(args)
+        .output()
+        .expect("binary should run")
+}
+
+#[test]
+fn test_recursive_flag_pair() {
+    assert!(parse(&["."]).is_recursive());
+    assert!(parse(&[".", "--recursive"]).is_recursive());
+    assert!(!parse(&[".", "--no-recursive"]).is_recursive());
+    // The last flag wins
+    assert!(parse(&[".", "--no-recursive", "-r"]).is_recursive());
+    assert!(!parse(&[".", "-r", "--no-recursive"]).is_recursive());
+}
+
+#[test]
+fn test_no_recursive_discovers_top_level_only() {
+    let dir = TempDir::new().unwrap();
+    fs::write(dir.path().join("top.txt"), "top\n").unwrap();
+    fs::create_dir_all(dir.path().join("nested")).unwrap();
+    fs::write(dir.path().join("nested/deep.txt"), "deep\n").unwrap();
+
+    let cli = parse(&[".", "--no-recursive"]);
+    let config = Config {
+        root_path: dir.path().to_string_lossy().to_string(),
+        is_recursive: cli.is_recursive(),
+        ..Default::default()
+    };
+    assert!(!config.is_recursive);
+
+    let ctx = FileContext::from_root(config, dir.path().to_str().unwrap()).unwrap();
+    let paths: Vec<&str> = ctx.file_entries.iter().map(|f| f.path.as_str()).collect();
+    assert_eq!(paths, vec!["top.txt"]);
+}
+
+#[test]
+fn test_output_size_report_format() {
+    assert_eq!(output_size_report("a\nb\n"), "Output size: 4 B, 2 lines");
+
+    let big = "x\n".repeat(629_146);
+    assert_eq!(
+        output_size_report(&big),
+        "Output size: 1.2 MB, 629,146 lines"
+    );
+}
+
+#[test]
+fn test_report_output_size_matches_written_output() {
+    let dir = init_repo(&[("main.rs", "fn main() {}\n")]);
+
+    let result = run_binary(&dir, &["main.rs", "-o", "context", "--report-output-size"]);
+    assert!(result.status.success());
+
+    let written = fs::read_to_string(dir.path().join("context.md")).unwrap();
+    let stderr = String::from_utf8(result.stderr).unwrap();
+    assert!(stderr.contains(&format!("Output size: {} B, ", written.len())));
+    assert!(stderr.contains(&output_size_report(&written)));
+}
+
+#[test]
+fn test_max_file_size_parses_human_sizes() {
+    assert_eq!(
+        parse(&[".", "--max-file-size", "2MB"]).max_file_size,
+        Some(2_000_000)
+    );
+    assert_eq!(
+        parse(&[".", "--max-file-size", "300kb"]).max_file_size,
+        Some(300_000)
+    );
+    assert_eq!(
+        parse(&[".", "--max-file-size", "1MiB"]).max_file_size,
+        Some(1 << 20)
+    );
+    assert_eq!(
+        parse(&[".", "--max-file-size", "1.5KB"]).max_file_size,
+        Some(1_500)
+    );
+    assert_eq!(
+        parse(&[".", "--max-file-size", "4096"]).max_file_size,
+        Some(4096)
+    );
+    assert_eq!(parse(&["."]).max_file_size, None);
+
+    assert!(Cli::try_parse_from(["repo-context", ".", "--max-file-size", "2XB"]).is_err());
+    assert!(Cli::try_parse_from(["repo-context", ".", "--max-file-size", "MB"]).is_err());
+}
+
+#[test]
+fn test_print_config_reflects_flags_and_presets() {
+    let dir = TempDir::new().unwrap();
+    let result = Command::new(env!("CARGO_BIN_EXE_rusty-repo-context-manager"))
+        .current_dir(dir.path())
+        .args([
+            ".",
+            "--exclude",
+            "target/**",
+            "--exclude-tests",
+            "--print-config",
+        ])
+        .output()
+        .expect("binary should run");
+    assert!(result.status.success());
+
+    let config: serde_json::Value = serde_json::from_slice(&result.stdout).unwrap();
+    let excludes: Vec<&str> = config["exclude_patterns"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|p| p.as_str().unwrap())
+        .collect();
+    assert_eq!(excludes[0], "target/**");
+    assert!(excludes.contains(&"tests/**"));
+    assert_eq!(config["is_recursive"], true);
+    assert_eq!(config["path_style"], "relative");
+    assert_eq!(config["target_paths"][0], ".");
+}
+
+#[test]
+fn test_recent_takes_optional_duration() {
+    const HOUR: u64 = 60 * 60;
+    assert_eq!(parse(&["."]).recent, None);
+    assert_eq!(
+        parse(&[".", "--recent"]).recent,
+        Some(Duration::from_secs(7 * 24 * HOUR))
+    );
+    assert_eq!(
+        parse(&[".", "--recent", "30d"]).recent,
+        Some(Duration::from_secs(30 * 24 * HOUR))
+    );
+    assert_eq!(
+        parse(&[".", "--recent", "12h"]).recent,
+        Some(Duration::from_secs(12 * HOUR))
+    );
+    assert_eq!(
+        parse(&[".", "--recent=45m"]).recent,
+        Some(Duration::from_secs(45 * 60))
+    );
+    assert!(Cli::try_parse_from(["repo-context", ".", "--recent", "3w"]).is_err());
+    // Rejected, not a panic
+    assert!(Cli::try_parse_from(["repo-context", ".", "--recent", "5é"]).is_err());
+    assert!(Cli::try_parse_from(["repo-context", ".", "--recent", "é"]).is_err());
+    assert!(Cli::try_parse_from(["repo-context", ".", "--recent", "99999999999999999d"]).is_err());
+    let err = utils::parse_duration("99999999999999999d").unwrap_err();
+    assert!(err.contains("too large"), "{}", err);
+}
+
+#[test]
+fn test_files_from_list() {
+    let dir = init_repo(&[
+        ("a.rs", "fn a() {}\n"),
+        ("b.rs", "fn b() {}\n"),
+        ("c.rs", "fn c() {}\n"),
+    ]);
+    fs::write(
+        dir.path().join("files.txt"),
+        "a.rs\n# comment\nb.rs\nmissing.rs\n",
+    )
+    .unwrap();
+
+    let strict = run_binary(&dir, &["--files-from", "files.txt", "-o", "context"]);
+    assert!(!strict.status.success());
+    assert!(String::from_utf8_lossy(&strict.stderr).contains("missing.rs"));
+
+    let lenient = run_binary(
+        &dir,
+        &[
+            "--files-from",
+            "files.txt",
+            "--ignore-missing",
+            "-o",
+            "context",
+        ],
+    );
+    assert!(lenient.status.success());
+    assert!(String::from_utf8_lossy(&lenient.stderr)
+        .contains("Warning: Listed path does not exist, skipping: missing.rs"));
+
+    let output = fs::read_to_string(dir.path().join("context.md")).unwrap();
+    assert!(output.contains("## FILE: a.rs"));
+    assert!(output.contains("## FILE: b.rs"));
+    assert!(!output.contains("## FILE: c.rs"));
+}
+
+#[test]
+fn test_clipboard_takes_precedence_over_output() {
+    let dir = init_repo(&[("main.rs", "fn main() {}\n")]);
+    let result = run_binary(&dir, &[".", "--clipboard", "-o", "out"]);
+    let stderr = String::from_utf8_lossy(&result.stderr);
+
+    // Nothing is written to the file, whether or not a clipboard is available
+    assert!(!dir.path().join("out.md").exists());
+
+    if cfg!(feature = "clipboard") {
+        // Headless systems report a clear error instead of panicking
+        assert!(
+            result.status.success() || stderr.contains("System clipboard is not available"),
+            "{}",
+            stderr
+        );
+    } else {
+        assert!(!result.status.success());
+        assert!(stderr.contains("requires building with the `clipboard` feature"));
+    }
+}
+
+#[test]
+fn test_stable_output_is_reproducible() {
+    let dir = init_repo(&[("b.rs", "fn b() {}\n"), ("a.rs", "fn a() {}\r\n")]);
+    let now = chrono::Utc::now().timestamp();
+    let generate = |extra: &[&str], epoch: i64| {
+        let mut args = vec![".", "--age", "--path-style", "absolute"];
+        args.extend_from_slice(extra);
+        let result = Command::new(env!("CARGO_BIN_EXE_rusty-repo-context-manager"))
+            .current_dir(dir.path())
+            .args(&args)
+            .env("SOURCE_DATE_EPOCH", epoch.to_string())
+            .output()
+            .expect("binary should run");
+        assert!(result.status.success());
+        String::from_utf8(result.stdout).unwrap()
+    };
+
+    // Ten days later the relative ages have moved on
+    let later = now + 10 * 24 * 60 * 60;
+    assert_ne!(generate(&[], now), generate(&[], later));
+
+    let first = generate(&["--stable"], now);
+    let second = generate(&["--stable"], later);
+    assert_eq!(first, second);
+
+    let root = dir.path().canonicalize().unwrap();
+    assert!(!first.contains(root.to_str().unwrap()));
+    assert!(!first.contains("days ago") && !first.contains("today"));
+    assert!(!first.contains('\r'));
+    assert!(first.find("FILE: a.rs").unwrap() < first.find("FILE: b.rs").unwrap());
+}
+
+#[test]
+fn test_stdout_output_matches_file_output() {
+    let dir = init_repo(&[("main.rs", "fn main() {}\n"), ("README.md", "# Demo\n")]);
+    let generate = |extra: &[&str]| {
+        let mut args = vec![".", "--stable"];
+        args.extend_from_slice(extra);
+        let result = Command::new(env!("CARGO_BIN_EXE_rusty-repo-context-manager"))
+            .current_dir(dir.path())
+            .args(&args)
+            .env("SOURCE_DATE_EPOCH", "1700000000")
+            .output()
+            .expect("binary should run");
+        assert!(result.status.success());
+        result.stdout
+    };
+
+    let stdout = generate(&[]);
+    assert!(generate(&["-o", "context"]).is_empty());
+    let written = fs::read(dir.path().join("context.md")).unwrap();
+    assert_eq!(stdout, written);
+}
+
+#[test]
+fn test_progress_is_silent_when_stderr_is_not_a_terminal() {
+    let dir = init_repo(&[("main.rs", "fn main() {}\n")]);
+
+    let result = run_binary(&dir, &[".", "--progress"]);
+    assert!(result.status.success());
+    assert!(result.stderr.is_empty());
+    assert!(String::from_utf8(result.stdout)
+        .unwrap()
+        .contains("FILE: main.rs"));
+}
+
+#[test]
+fn test_config_file_provides_defaults() {
+    let dir = init_repo(&[
+        ("main.rs", "fn main() {}\n"),
+        ("notes.txt", "notes\n"),
+        (
+            ".repo-context.toml",
+            "include_patterns = [\"*.rs\"]\nline_numbers = true\n",
+        ),
+    ]);
+
+    let result = run_binary(&dir, &["."]);
+    assert!(result.status.success());
+    let output = String::from_utf8(result.stdout).unwrap();
+    assert!(output.contains("FILE: main.rs"));
+    assert!(output.contains("1: fn main() {}\n"));
+    assert!(!output.contains("FILE: notes.txt"));
+
+    // Flags override the file
+    let result = run_binary(&dir, &[".", "-i", "*.txt"]);
+    let output = String::from_utf8(result.stdout).unwrap();
+    assert!(output.contains("FILE: notes.txt"));
+    assert!(!output.contains("FILE: main.rs"));
+
+    // An explicit --config must exist, and unknown keys are rejected
+    let result = run_binary(&dir, &[".", "--config", "missing.toml"]);
+    assert!(!result.status.success());
+    fs::write(dir.path().join("typo.toml"), "include = [\"*.rs\"]\n").unwrap();
+    let result = run_binary(&dir, &[".", "--config", "typo.toml"]);
+    assert!(!result.status.success());
+    assert!(String::from_utf8(result.stderr)
+        .unwrap()
+        .contains("Failed to parse config file typo.toml"));
+}
+
+#[test]
+fn test_target_paths_from_stdin() {
+    let dir = init_repo(&[
+        ("a.rs", "fn a() {}\n"),
+        ("b.rs", "fn b() {}\n"),
+        ("c.txt", "c\n"),
+    ]);
+    let run_with_stdin = |args: &[&str], input: &str| {
+        let mut child = Command::new(env!("CARGO_BIN_EXE_rusty-repo-context-manager"))
+            .current_dir(dir.path())
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .expect("binary should run");
+        child
+            .stdin
+            .take()
+            .unwrap()
+            .write_all(input.as_bytes())
+            .unwrap();
+        child.wait_with_output().unwrap()
+    };
+
+    let result = run_with_stdin(&["--stdin"], "  a.rs  \n\n c.txt\n");
+    assert!(result.status.success());
+    let output = String::from_utf8(result.stdout).unwrap();
+    assert!(output.contains("FILE: a.rs"));
+    assert!(output.contains("FILE: c.txt"));
+    assert!(!output.contains("FILE: b.rs"));
+
+    // `-` mixes piped paths with ones given on the command line
+    let result = run_with_stdin(&["b.rs", "-"], "a.rs\n");
+    let output = String::from_utf8(result.stdout).unwrap();
+    assert!(output.contains("FILE: a.rs") && output.contains("FILE: b.rs"));
+    assert!(!output.contains("FILE: c.txt"));
+
+    // Nothing piped is an error rather than packaging the whole repository
+    let result = run_with_stdin(&["--stdin"], "\n");
+    assert!(!result.status.success());
+}
+
+#[test]
+fn test_dry_run_lists_files_without_writing_output() {
+    let dir = init_repo(&[
+        ("main.rs", "fn main() {}\n"),
+        ("lib.rs", "pub fn a() {}\npub fn b() {}\n"),
+    ]);
+
+    let result = run_binary(&dir, &[".", "--dry-run", "-o", "context"]);
+    assert!(result.status.success());
+    let stdout = String::from_utf8(result.stdout).unwrap();
+    let lines: Vec<&str> = stdout.lines().collect();
+    assert_eq!(
+        lines,
+        vec![
+            "lib.rs         28 B         2 lines",
+            "main.rs        13 B         1 lines",
+            "2 file(s), 41 B, 3 lines",
+        ]
+    );
+    assert!(!dir.path().join("context.md").exists());
+}
+
+#[test]
+fn test_max_files_stops_discovery_with_warning() {
+    let files: Vec<(String, String)> = (0..10)
+        .map(|i| (format!("file{}.txt", i), format!("file {}\n", i)))
+        .collect();
+    let files: Vec<(&str, &str)> = files
+        .iter()
+        .map(|(p, c)| (p.as_str(), c.as_str()))
+        .collect();
+    let dir = init_repo(&files);
+
+    let result = run_binary(&dir, &[".", "--dry-run", "--max-files", "3"]);
+    assert!(result.status.success());
+    let stdout = String::from_utf8(result.stdout).unwrap();
+    assert!(stdout.ends_with("3 file(s), 21 B, 3 lines\n"));
+    assert!(stdout.starts_with("file0.txt"));
+    let stderr = String::from_utf8(result.stderr).unwrap();
+    assert!(stderr.contains("--max-files limit reached"));
+
+    // Exactly at the limit nothing was cut, so there is no warning
+    let result = run_binary(&dir, &[".", "--dry-run", "--max-files", "10"]);
+    let stderr = String::from_utf8(result.stderr).unwrap();
+    assert!(!stderr.contains("--max-files"));
+}
+
+#[test]
+fn test_max_files_caps_all_targets_together() {
+    let dir = init_repo(&[
+        ("a/f0.txt", "x\n"),
+        ("a/f1.txt", "x\n"),
+        ("a/f2.txt", "x\n"),
+        ("b/f0.txt", "x\n"),
+        ("b/f1.txt", "x\n"),
+        ("b/f2.txt", "x\n"),
+        ("top.txt", "x\n"),
+    ]);
+
+    let result = run_binary(&dir, &["a", "b", "--dry-run", "--max-files", "4"]);
+    assert!(result.status.success());
+    let stdout = String::from_utf8(result.stdout).unwrap();
+    assert!(stdout.ends_with("4 file(s), 8 B, 4 lines\n"), "{}", stdout);
+    assert!(stdout.contains("a/f2.txt") && stdout.contains("b/f0.txt"));
+    assert!(!stdout.contains("b/f1.txt"));
+
+    // File targets count towards the same limit
+    let result = run_binary(&dir, &["a", "top.txt", "--dry-run", "--max-files", "3"]);
+    let stdout = String::from_utf8(result.stdout).unwrap();
+    assert!(stdout.ends_with("3 file(s), 6 B, 3 lines\n"), "{}", stdout);
+    assert!(!stdout.contains("top.txt"));
+    let stderr = String::from_utf8(result.stderr).unwrap();
+    assert!(stderr.contains("top.txt (--max-files limit reached)"));
+}
+
+#[test]
+fn test_gzip_output_decompresses_to_markdown() {
+    use flate2::read::GzDecoder;
+    use std::io::Read;
+
+    let dir = init_repo(&[("main.rs", "fn main() {}\n"), ("notes.md", "# Notes\n")]);
+
+    // The first output file would otherwise be packaged by the second run
+    let result = run_binary(&dir, &[".", "-o", "plain", "--exclude", "plain.md"]);
+    assert!(result.status.success());
+    let result = run_binary(
+        &dir,
+        &[".", "-o", "context", "--gzip", "--exclude", "plain.md"],
+    );
+    assert!(result.status.success());
+    assert!(!dir.path().join("context.md").exists());
+
+    let compressed = fs::read(dir.path().join("context.md.gz")).unwrap();
+    let mut markdown = String::new();
+    _ = GzDecoder::new(compressed.as_slice())
+        .read_to_string(&mut markdown)
+        .unwrap();
+    let plain = fs::read_to_string(dir.path().join("plain.md")).unwrap();
+    assert!(markdown.contains("fn main() {}"));
+    assert_eq!(markdown, plain);
+}
+
+#[test]
+fn test_tee_writes_stdout_and_file() {
+    let dir = init_repo(&[("main.rs", "fn main() {}\n"), ("notes.md", "# Notes\n")]);
+
+    let result = run_binary(&dir, &["main.rs", "notes.md", "-o", "context", "--tee"]);
+    assert!(result.status.success());
+
+    let stdout = String::from_utf8(result.stdout).unwrap();
+    let written = fs::read_to_string(dir.path().join("context.md")).unwrap();
+    assert!(written.contains("fn main() {}"));
+    assert!(written.contains("# Notes"));
+    assert_eq!(stdout, written);
+}
+
+#[test]
+fn test_no_git_packages_a_plain_directory() {
+    // No `Repository::init`: the directory is not part of any repository
+    let dir = TempDir::new().unwrap();
+    fs::create_dir(dir.path().join("src")).unwrap();
+    fs::write(dir.path().join("src/main.rs"), "fn main() {}\n").unwrap();
+    fs::write(dir.path().join("notes.md"), "# Notes\n").unwrap();
+
+    let result = run_binary(&dir, &[".", "--no-git", "--age"]);
+    assert!(
+        result.status.success(),
+        "{}",
+        String::from_utf8_lossy(&result.stderr)
+    );
+
+    let stdout = String::from_utf8(result.stdout).unwrap();
+    assert!(stdout.contains("FILE: src/main.rs"));
+    assert!(stdout.contains("fn main() {}"));
+    assert!(stdout.contains("# Notes"));
+    assert!(stdout.contains("Git information disabled (--no-git)."));
+    let stderr = String::from_utf8(result.stderr).unwrap();
+    assert!(stderr.contains("Warning: --no-git ignores options that need git: --age"));
+}
+
+#[test]
+fn test_html_warns_about_markdown_only_options() {
+    let dir = init_repo(&[("main.rs", "fn main() {}\n"), ("copy.rs", "fn main() {}\n")]);
+
+    let result = run_binary(
+        &dir,
+        &[
+            ".",
+            "--format",
+            "html",
+            "--dedupe",
+            "--max-tokens",
+            "5",
+            "--binary",
+            "base64",
+            "--todos",
+            "--git-status",
+        ],
+    );
+    assert!(result.status.success());
+    let stderr = String::from_utf8(result.stderr).unwrap();
+    assert!(stderr.contains(
+        "Warning: --format html ignores these markdown-only options: --max-tokens, --dedupe, --binary, --todos, --git-status"
+    ));
+
+    let result = run_binary(&dir, &[".", "--dedupe"]);
+    assert!(!String::from_utf8(result.stderr)
+        .unwrap()
+        .contains("--format html"));
+}
@@ -13,6 +13,7 @@
 //===----------------------------------------------------------------------===//
 
 use rusty_repo_context_manager::types::{Config, FileContext, GitInfo, RepositoryContext};
+use rusty_repo_context_manager::utils::fence_language;
 use rusty_repo_context_manager::{ContextManager, OutputContext, OutputDestination, OutputFormat};
 use std::fs;
 use tempfile::TempDir;
@@ -46,6 +47,7 @@ fn test_code_block_formatting() {
         is_recursive: false,
         show_line_numbers: false,
         recent_only: false,
+        ..Default::default()
     };
 
     let file_ctx = FileContext::from_root(config.clone(), temp_dir.path().to_str().unwrap())
@@ -58,6 +60,7 @@ fn test_code_block_formatting() {
         author: None,
         email: None,
         date: None,
+        ..Default::default()
     };
 
     let repo_context = RepositoryContext {
@@ -65,6 +68,7 @@ fn test_code_block_formatting() {
         git_info,
         file_ctx,
         tree_repr: String::new(), // Use empty tree representation for test
+        ..Default::default()
     };
 
     let mut context_manager = ContextManager::new(config);
@@ -85,8 +89,8 @@ fn test_code_block_formatting() {
         fs::read_to_string(format!("{}.md", output_path)).expect("Failed to read generated file");
 
     // Verify code blocks have proper language specifiers
-    assert!(generated_content.contains("```rs\n"));
-    assert!(generated_content.contains("```py\n"));
+    assert!(generated_content.contains("```rust\n"));
+    assert!(generated_content.contains("```python\n"));
     assert!(generated_content.contains("fn main() {"));
     assert!(generated_content.contains("print(\"Hello, Python!\")"));
 }
@@ -109,6 +113,7 @@ fn test_file_without_extension() {
         is_recursive: false,
         show_line_numbers: false,
         recent_only: false,
+        ..Default::default()
     };
 
     let file_ctx = FileContext::from_root(config.clone(), temp_dir.path().to_str().unwrap())
@@ -121,6 +126,7 @@ fn test_file_without_extension() {
         author: None,
         email: None,
         date: None,
+        ..Default::default()
     };
 
     let repo_context = RepositoryContext {
@@ -128,6 +134,7 @@ fn test_file_without_extension() {
         git_info,
         file_ctx,
         tree_repr: String::new(), // Use empty tree representation for test
+        ..Default::default()
     };
 
     let mut context_manager = ContextManager::new(config);
@@ -151,3 +158,56 @@ fn test_file_without_extension() {
     assert!(generated_content.contains("```\n"));
     assert!(generated_content.contains("This is a README file"));
 }
+
+#[test]
+fn test_content_with_backtick_fences() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+
+    let markdown = "# Usage\n\n```bash\ncargo run\n```\n";
+    fs::write(temp_dir.path().join("USAGE.md"), markdown).expect("Failed to write file");
+
+    let config = Config {
+        root_path: temp_dir.path().to_string_lossy().to_string(),
+        ..Default::default()
+    };
+    let file_ctx = FileContext::from_root(config.clone(), temp_dir.path().to_str().unwrap())
+        .expect("Failed to create FileContext");
+
+    let mut context_manager = ContextManager::new(config);
+    context_manager.context = Some(RepositoryContext {
+        root_path: temp_dir.path().to_string_lossy().to_string(),
+        file_ctx,
+        ..Default::default()
+    });
+
+    let output_path = temp_dir
+        .path()
+        .join("test_output")
+        .to_string_lossy()
+        .to_string();
+    OutputContext::new(context_manager)
+        .format(OutputFormat::Markdown)
+        .destination(OutputDestination::File(output_path.clone()))
+        .generate()
+        .expect("Failed to generate output");
+
+    let generated_content =
+        fs::read_to_string(format!("{}.md", output_path)).expect("Failed to read generated file");
+
+    // The outer fence must be longer than any backtick run in the content
+    assert!(generated_content.contains(&format!("````markdown\n{}````\n", markdown)));
+}
+
+#[test]
+fn test_fence_language_mapping() {
+    assert_eq!(fence_language("src/main.rs"), "rust");
+    assert_eq!(fence_language("scripts/build.py"), "python");
+    assert_eq!(fence_language("web/app.ts"), "typescript");
+    assert_eq!(fence_language(".github/workflows/ci.yml"), "yaml");
+    assert_eq!(fence_language("README.MD"), "markdown");
+    // Extensions that already are fence identifiers, or are unknown, are kept as is
+    assert_eq!(fence_language("main.go"), "go");
+    assert_eq!(fence_language("notes.txt"), "txt");
+    assert_eq!(fence_language("Makefile"), "");
+    assert_eq!(fence_language("v1.2/Makefile"), "");
+}
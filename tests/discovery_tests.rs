@@ -0,0 +1,799 @@
+//===----------------------------------------------------------------------===//
+//
+// Copyright (c) 2025 David Rivera
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+//
+// SPDX-License-Identifier: MIT
+//
+//===----------------------------------------------------------------------===//
+//
+// Integration tests for file discovery filters
+//===----------------------------------------------------------------------===//
+//
+
+use filetime::FileTime;
+use rusty_repo_context_manager::files::load_ignore_file;
+use rusty_repo_context_manager::types::{
+    Config, FileContext, LineEnding, ProgressEvent, ProgressHook,
+};
+use rusty_repo_context_manager::TreeContext;
+use std::fs;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+use tempfile::TempDir;
+
+/// Create the given files under a fresh temp directory
+fn setup_dir(files: &[(&str, &str)]) -> TempDir {
+    let dir = TempDir::new().expect("Failed to create temp dir");
+    for (path, content) in files {
+        let full_path = dir.path().join(path);
+        if let Some(parent) = full_path.parent() {
+            fs::create_dir_all(parent).unwrap();
+        }
+        fs::write(&full_path, content).unwrap();
+    }
+    dir
+}
+
+fn discover(dir: &TempDir, config: Config) -> FileContext {
+    FileContext::from_root(config, dir.path().to_str().unwrap())
+        .expect("Failed to create FileContext")
+}
+
+fn base_config(dir: &TempDir) -> Config {
+    Config {
+        root_path: dir.path().to_string_lossy().to_string(),
+        ..Default::default()
+    }
+}
+
+fn sorted_paths(ctx: &FileContext) -> Vec<String> {
+    let mut paths: Vec<String> = ctx.file_entries.iter().map(|f| f.path.clone()).collect();
+    paths.sort();
+    paths
+}
+
+#[test]
+fn test_only_hashes_filter() {
+    let dir = setup_dir(&[
+        ("a.txt", "alpha\n"),
+        ("b.txt", "beta\n"),
+        ("c.txt", "gamma\n"),
+    ]);
+
+    // First pass: record the hashes of two files
+    let ctx = discover(&dir, base_config(&dir));
+    let hash_of = |name: &str| {
+        ctx.file_entries
+            .iter()
+            .find(|f| f.path == name)
+            .and_then(|f| f.content_hash.clone())
+            .unwrap()
+    };
+    let (hash_a, hash_b) = (hash_of("a.txt"), hash_of("b.txt"));
+    assert_ne!(hash_a, hash_b);
+
+    // Second pass: only package the file matching one of those hashes
+    let hash_list = TempDir::new().unwrap();
+    let list_path = hash_list.path().join("hashes.txt");
+    fs::write(
+        &list_path,
+        format!("# audited set\n{}  b.txt\n{}\n", hash_b, "0".repeat(64)),
+    )
+    .unwrap();
+
+    let ctx = discover(
+        &dir,
+        Config {
+            only_hashes: Some(list_path.to_string_lossy().to_string()),
+            ..base_config(&dir)
+        },
+    );
+    assert_eq!(sorted_paths(&ctx), vec!["b.txt"]);
+}
+
+#[test]
+fn test_invalid_utf8_is_decoded_lossily() {
+    let dir = setup_dir(&[("valid.txt", "all good\n")]);
+    fs::write(dir.path().join("broken.txt"), b"hello \xff world\n").unwrap();
+
+    let ctx = discover(&dir, base_config(&dir));
+
+    let broken = ctx
+        .file_entries
+        .iter()
+        .find(|f| f.path == "broken.txt")
+        .unwrap();
+    assert!(!broken.is_binary);
+    assert!(broken.content_lossy);
+    assert_eq!(broken.content.as_deref(), Some("hello \u{FFFD} world\n"));
+
+    let valid = ctx
+        .file_entries
+        .iter()
+        .find(|f| f.path == "valid.txt")
+        .unwrap();
+    assert!(!valid.content_lossy);
+}
+
+#[test]
+fn test_trim_outliers() {
+    let huge = "x".repeat(10_000);
+    let dir = setup_dir(&[
+        ("a.txt", "small file\n"),
+        ("b.txt", "another small\n"),
+        ("c.txt", "tiny\n"),
+        ("huge.txt", &huge),
+    ]);
+
+    let ctx = discover(
+        &dir,
+        Config {
+            trim_outliers: Some(10.0),
+            ..base_config(&dir)
+        },
+    );
+
+    assert_eq!(ctx.file_entries.len(), 4);
+    for file in &ctx.file_entries {
+        if file.path == "huge.txt" {
+            assert!(file.content.is_none());
+            assert!(file.omitted_reason.as_ref().unwrap().contains("outlier"));
+        } else {
+            assert!(file.content.is_some(), "{} should keep content", file.path);
+            assert!(file.omitted_reason.is_none());
+        }
+    }
+}
+
+#[test]
+fn test_exclude_empty_files() {
+    let dir = setup_dir(&[
+        ("empty.txt", ""),
+        ("blank.txt", "  \n\t\n"),
+        ("normal.txt", "content\n"),
+        ("empty.bin", ""),
+    ]);
+
+    let ctx = discover(&dir, base_config(&dir));
+    assert_eq!(ctx.file_entries.len(), 4);
+
+    let ctx = discover(
+        &dir,
+        Config {
+            exclude_empty: true,
+            ..base_config(&dir)
+        },
+    );
+    assert_eq!(sorted_paths(&ctx), vec!["normal.txt"]);
+}
+
+#[test]
+fn test_min_lines_drops_short_text_files() {
+    let dir = setup_dir(&[
+        ("one.toml", "name = \"demo\"\n"),
+        ("two.rs", "fn main() {\n}\n"),
+        ("image.bin", "\0\x01\x02"),
+    ]);
+
+    let ctx = discover(
+        &dir,
+        Config {
+            min_lines: Some(2),
+            ..base_config(&dir)
+        },
+    );
+    assert_eq!(sorted_paths(&ctx), vec!["image.bin", "two.rs"]);
+
+    let mut tree = TreeContext::new(Config {
+        min_lines: Some(2),
+        ..base_config(&dir)
+    });
+    let tree_str = tree.build_tree_from_root().unwrap().tree_str.clone();
+    assert!(!tree_str.contains("one.toml"));
+    assert!(tree_str.contains("two.rs"));
+}
+
+#[test]
+fn test_skip_data_omits_large_data_files() {
+    let rows: String = (0..1000)
+        .map(|i| format!("  {{\"id\": {}}},\n", i))
+        .collect();
+    let large = format!("[\n{}  {{}}\n]\n", rows);
+    let dir = setup_dir(&[
+        ("large.json", &large),
+        ("small.json", "{\"name\": \"demo\"}\n"),
+        ("code.rs", &"// comment\n".repeat(1000)),
+    ]);
+
+    let ctx = discover(
+        &dir,
+        Config {
+            skip_data: true,
+            ..base_config(&dir)
+        },
+    );
+    assert_eq!(
+        sorted_paths(&ctx),
+        vec!["code.rs", "large.json", "small.json"]
+    );
+
+    for file in &ctx.file_entries {
+        if file.path == "large.json" {
+            assert!(file.content.is_none());
+            assert!(file.omitted_reason.as_ref().unwrap().contains("data file"));
+        } else {
+            assert!(file.content.is_some(), "{} should keep content", file.path);
+        }
+    }
+}
+
+#[test]
+fn test_exclude_contains_substring() {
+    let dir = setup_dir(&[
+        ("src/main.rs", "fn main() {}\n"),
+        ("tests/integration.rs", "#[test]\nfn it_works() {}\n"),
+    ]);
+
+    let ctx = discover(
+        &dir,
+        Config {
+            exclude_contains: vec!["test".into()],
+            ..base_config(&dir)
+        },
+    );
+    assert_eq!(sorted_paths(&ctx), vec!["src/main.rs"]);
+}
+
+#[test]
+fn test_primary_lang_only() {
+    let dir = setup_dir(&[
+        ("src/main.rs", "fn main() {\n    run();\n}\n"),
+        ("src/lib.rs", "pub fn run() {\n    println!(\"hi\");\n}\n"),
+        ("scripts/tool.py", "print('hi')\n"),
+        ("README.md", "# Demo\n"),
+        ("notes.txt", "misc\n"),
+    ]);
+
+    let ctx = discover(
+        &dir,
+        Config {
+            primary_lang_only: true,
+            ..base_config(&dir)
+        },
+    );
+    assert_eq!(
+        sorted_paths(&ctx),
+        vec!["README.md", "src/lib.rs", "src/main.rs"]
+    );
+}
+
+#[test]
+fn test_max_content_bytes() {
+    let dir = setup_dir(&[("small.txt", "tiny\n"), ("big.txt", &"line\n".repeat(100))]);
+
+    let ctx = discover(
+        &dir,
+        Config {
+            max_content_bytes: Some(100),
+            ..base_config(&dir)
+        },
+    );
+
+    let big = ctx
+        .file_entries
+        .iter()
+        .find(|f| f.path == "big.txt")
+        .unwrap();
+    assert!(big.content.is_none());
+    assert_eq!(big.size, 500);
+    assert_eq!(big.lines, 100);
+    assert!(big
+        .omitted_reason
+        .as_ref()
+        .unwrap()
+        .contains("100 byte limit"));
+
+    let small = ctx
+        .file_entries
+        .iter()
+        .find(|f| f.path == "small.txt")
+        .unwrap();
+    assert_eq!(small.content.as_deref(), Some("tiny\n"));
+    assert!(small.omitted_reason.is_none());
+
+    // Without a limit the 1MB default applies
+    let ctx = discover(&dir, base_config(&dir));
+    assert!(ctx.file_entries.iter().all(|f| f.content.is_some()));
+}
+
+/// Set a file's modification time to `age` ago
+fn set_age(dir: &TempDir, name: &str, age: Duration) {
+    let mtime = FileTime::from_system_time(SystemTime::now() - age);
+    filetime::set_file_mtime(dir.path().join(name), mtime).unwrap();
+}
+
+#[test]
+fn test_recent_window_boundaries() {
+    const HOUR: u64 = 60 * 60;
+    const DAY: u64 = 24 * HOUR;
+    let dir = setup_dir(&[
+        ("fresh.txt", "a\n"),
+        ("six_days.txt", "b\n"),
+        ("eight_days.txt", "c\n"),
+        ("just_inside_30d.txt", "d\n"),
+        ("just_outside_30d.txt", "e\n"),
+    ]);
+    set_age(&dir, "fresh.txt", Duration::from_secs(HOUR));
+    set_age(&dir, "six_days.txt", Duration::from_secs(6 * DAY));
+    set_age(&dir, "eight_days.txt", Duration::from_secs(8 * DAY));
+    set_age(
+        &dir,
+        "just_inside_30d.txt",
+        Duration::from_secs(30 * DAY - HOUR),
+    );
+    set_age(
+        &dir,
+        "just_outside_30d.txt",
+        Duration::from_secs(30 * DAY + HOUR),
+    );
+
+    let recent = |window: Option<Duration>| {
+        sorted_paths(&discover(
+            &dir,
+            Config {
+                recent_only: true,
+                recent_window: window,
+                ..base_config(&dir)
+            },
+        ))
+    };
+
+    // Without a window the 7 day default applies
+    assert_eq!(recent(None), vec!["fresh.txt", "six_days.txt"]);
+    assert_eq!(
+        recent(Some(Duration::from_secs(30 * DAY))),
+        vec![
+            "eight_days.txt",
+            "fresh.txt",
+            "just_inside_30d.txt",
+            "six_days.txt"
+        ]
+    );
+    assert_eq!(
+        recent(Some(Duration::from_secs(2 * HOUR))),
+        vec!["fresh.txt"]
+    );
+}
+
+#[test]
+fn test_token_estimates_grow_with_content() {
+    let dir = setup_dir(&[
+        ("empty.txt", ""),
+        ("short.txt", "fn main() {}\n"),
+        ("long.txt", &"fn main() {}\n".repeat(50)),
+    ]);
+    fs::write(dir.path().join("data.bin"), b"\0\x01\x02\x03".repeat(100)).unwrap();
+
+    let ctx = discover(&dir, base_config(&dir));
+    let tokens = |name: &str| {
+        ctx.file_entries
+            .iter()
+            .find(|f| f.path == name)
+            .unwrap()
+            .tokens
+    };
+
+    assert_eq!(tokens("empty.txt"), 0);
+    assert!(tokens("short.txt") > 0);
+    assert!(tokens("long.txt") >= tokens("short.txt"));
+    assert_eq!(tokens("data.bin"), 0);
+}
+
+#[test]
+fn test_line_ending_detection() {
+    let dir = setup_dir(&[
+        ("unix.txt", "one\ntwo\nthree\n"),
+        ("windows.txt", "one\r\ntwo\r\nthree\r\n"),
+        ("mixed.txt", "one\r\ntwo\nthree\n"),
+        ("single.txt", "no line break"),
+    ]);
+
+    let ctx = discover(&dir, base_config(&dir));
+    let entry = |name: &str| ctx.file_entries.iter().find(|f| f.path == name).unwrap();
+
+    assert_eq!(entry("unix.txt").line_ending, LineEnding::Lf);
+    assert_eq!(entry("windows.txt").line_ending, LineEnding::CrLf);
+    assert_eq!(entry("mixed.txt").line_ending, LineEnding::Mixed);
+    assert_eq!(entry("single.txt").line_ending, LineEnding::Lf);
+
+    // CRLF pairs count as a single line break
+    for name in ["unix.txt", "windows.txt", "mixed.txt"] {
+        assert_eq!(entry(name).lines, 3, "{}", name);
+    }
+}
+
+#[test]
+fn test_hidden_files_are_opt_in() {
+    let dir = setup_dir(&[
+        ("src/main.rs", "fn main() {}\n"),
+        (".config/app.toml", "debug = true\n"),
+        (".env.example", "TOKEN=\n"),
+    ]);
+
+    let ctx = discover(&dir, base_config(&dir));
+    assert_eq!(sorted_paths(&ctx), vec!["src/main.rs"]);
+
+    let hidden = |config: Config| {
+        sorted_paths(&discover(
+            &dir,
+            Config {
+                include_hidden: true,
+                ..config
+            },
+        ))
+    };
+    assert_eq!(
+        hidden(base_config(&dir)),
+        vec![".config/app.toml", ".env.example", "src/main.rs"]
+    );
+
+    // Exclude globs still apply to hidden entries
+    assert_eq!(
+        hidden(Config {
+            exclude_patterns: vec![".config/**".into()],
+            ..base_config(&dir)
+        }),
+        vec![".env.example", "src/main.rs"]
+    );
+}
+
+#[test]
+fn test_parallel_discovery_matches_single_threaded() {
+    let files: Vec<(String, String)> = (0..200)
+        .map(|i| {
+            (
+                format!("dir{}/sub{}/file{}.rs", i % 7, i % 3, i),
+                format!("fn f{}() {{}}\n", i).repeat(i % 5 + 1),
+            )
+        })
+        .collect();
+    let files: Vec<(&str, &str)> = files
+        .iter()
+        .map(|(path, content)| (path.as_str(), content.as_str()))
+        .collect();
+    let dir = setup_dir(&files);
+
+    let parallel = discover(&dir, base_config(&dir));
+    let sequential = discover(
+        &dir,
+        Config {
+            single_threaded: true,
+            ..base_config(&dir)
+        },
+    );
+
+    assert_eq!(parallel.file_entries.len(), 200);
+    assert_eq!(
+        format!("{:?}", parallel.file_entries),
+        format!("{:?}", sequential.file_entries)
+    );
+    // Entries come out sorted by path regardless of directory read order
+    let paths: Vec<String> = parallel
+        .file_entries
+        .iter()
+        .map(|f| f.path.clone())
+        .collect();
+    assert_eq!(paths, sorted_paths(&parallel));
+}
+
+#[test]
+fn test_utf16_files_are_decoded_as_text() {
+    let text = "fn main() {\n    println!(\"héllo\");\n}\n";
+    let encode = |bom: [u8; 2], le: bool| {
+        let mut bytes = bom.to_vec();
+        for unit in text.encode_utf16() {
+            bytes.extend(if le {
+                unit.to_le_bytes()
+            } else {
+                unit.to_be_bytes()
+            });
+        }
+        bytes
+    };
+    let dir = TempDir::new().unwrap();
+    fs::write(dir.path().join("le.rs"), encode([0xFF, 0xFE], true)).unwrap();
+    fs::write(dir.path().join("be.rs"), encode([0xFE, 0xFF], false)).unwrap();
+    // Null bytes without a byte order mark are still treated as binary
+    fs::write(dir.path().join("raw.bin"), &encode([0xFF, 0xFE], true)[2..]).unwrap();
+
+    let ctx = discover(&dir, base_config(&dir));
+    let entry = |path: &str| ctx.file_entries.iter().find(|f| f.path == path).unwrap();
+
+    for path in ["le.rs", "be.rs"] {
+        let file = entry(path);
+        assert!(!file.is_binary, "{} should be text", path);
+        assert_eq!(file.content.as_deref(), Some(text));
+        assert_eq!(file.lines, 3);
+        assert!(!file.content_lossy);
+    }
+    assert!(entry("raw.bin").is_binary);
+}
+
+#[test]
+fn test_progress_hook_reports_discovery() {
+    let dir = setup_dir(&[("a.rs", "a\n"), ("src/b.rs", "b\n"), ("src/c.rs", "c\n")]);
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let recorded = Arc::clone(&events);
+
+    let ctx = discover(
+        &dir,
+        Config {
+            progress: Some(ProgressHook::new(move |event| {
+                recorded.lock().unwrap().push(event)
+            })),
+            ..base_config(&dir)
+        },
+    );
+    assert_eq!(ctx.file_entries.len(), 3);
+
+    let events = events.lock().unwrap();
+    let discovered: Vec<usize> = events
+        .iter()
+        .filter_map(|event| match event {
+            ProgressEvent::Discovered(found) => Some(*found),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(discovered, vec![1, 2, 3]);
+
+    // Files may finish in any order when read in parallel, but every one is reported once
+    let mut processed: Vec<usize> = events
+        .iter()
+        .filter_map(|event| match event {
+            ProgressEvent::Processed { done, total } => {
+                assert_eq!(*total, 3);
+                Some(*done)
+            }
+            _ => None,
+        })
+        .collect();
+    processed.sort();
+    assert_eq!(processed, vec![1, 2, 3]);
+}
+
+#[test]
+fn test_dry_run_skips_file_content() {
+    let dir = setup_dir(&[("a.rs", "fn a() {}\nfn b() {}\n"), ("empty.rs", "  \n")]);
+
+    let ctx = discover(
+        &dir,
+        Config {
+            dry_run: true,
+            ..base_config(&dir)
+        },
+    );
+    let entry = ctx.file_entries.iter().find(|f| f.path == "a.rs").unwrap();
+    assert_eq!(entry.content, None);
+    assert_eq!(entry.content_hash, None);
+    assert_eq!(entry.lines, 2);
+
+    // Filters that look at the content still get it
+    let ctx = discover(
+        &dir,
+        Config {
+            dry_run: true,
+            exclude_empty: true,
+            ..base_config(&dir)
+        },
+    );
+    let paths: Vec<&str> = ctx.file_entries.iter().map(|f| f.path.as_str()).collect();
+    assert_eq!(paths, vec!["a.rs"]);
+}
+
+#[test]
+fn test_extension_filters_intersect_with_globs() {
+    let dir = setup_dir(&[
+        ("src/main.rs", "fn main() {}\n"),
+        ("src/Lib.RS", "pub fn lib() {}\n"),
+        ("src/config.toml", "a = 1\n"),
+        ("docs/guide.rs", "// guide\n"),
+        ("build.log", "log\n"),
+        ("Makefile", "all:\n"),
+    ]);
+    let paths = |config: Config| sorted_paths(&discover(&dir, config));
+
+    assert_eq!(
+        paths(Config {
+            include_exts: vec!["rs".to_string(), ".toml".to_string()],
+            ..base_config(&dir)
+        }),
+        vec![
+            "docs/guide.rs",
+            "src/Lib.RS",
+            "src/config.toml",
+            "src/main.rs"
+        ]
+    );
+
+    // A file has to satisfy both the extension list and the include globs
+    assert_eq!(
+        paths(Config {
+            include_exts: vec!["rs".to_string()],
+            include_patterns: vec!["src/*".to_string()],
+            ..base_config(&dir)
+        }),
+        vec!["src/Lib.RS", "src/main.rs"]
+    );
+
+    assert_eq!(
+        paths(Config {
+            exclude_exts: vec!["LOG".to_string(), "rs".to_string()],
+            ..base_config(&dir)
+        }),
+        vec!["Makefile", "src/config.toml"]
+    );
+}
+
+#[test]
+fn test_skip_larger_than_leaves_big_files_out() {
+    let big = "x".repeat(2048);
+    let dir = setup_dir(&[("small.txt", "tiny\n"), ("big.txt", big.as_str())]);
+
+    let ctx = discover(
+        &dir,
+        Config {
+            skip_larger_than: Some(1024),
+            ..base_config(&dir)
+        },
+    );
+
+    assert_eq!(sorted_paths(&ctx), vec!["small.txt"]);
+    assert_eq!(ctx.skipped_by_size, 1);
+}
+
+#[test]
+fn test_file_entries_are_sorted_by_path() {
+    let dir = setup_dir(&[
+        ("zeta.txt", "z\n"),
+        ("src/b.rs", "b\n"),
+        ("alpha.txt", "a\n"),
+        ("src/a.rs", "a\n"),
+        ("Mixed.txt", "m\n"),
+    ]);
+    let paths = |ctx: &FileContext| -> Vec<String> {
+        ctx.file_entries.iter().map(|f| f.path.clone()).collect()
+    };
+    let expected = vec!["Mixed.txt", "alpha.txt", "src/a.rs", "src/b.rs", "zeta.txt"];
+
+    assert_eq!(paths(&discover(&dir, base_config(&dir))), expected);
+
+    // Targets given out of order are merged into one sorted list
+    let root = dir.path().to_string_lossy().to_string();
+    let ctx = FileContext::from_target_paths(
+        Config {
+            target_paths: vec![
+                "zeta.txt".to_string(),
+                "src/b.rs".to_string(),
+                "alpha.txt".to_string(),
+                "src/a.rs".to_string(),
+                "Mixed.txt".to_string(),
+            ],
+            ..base_config(&dir)
+        },
+        &root,
+    )
+    .unwrap();
+    assert_eq!(paths(&ctx), expected);
+}
+
+#[test]
+fn test_ignore_file_excludes_matching_paths() {
+    let dir = setup_dir(&[
+        (
+            ".repo-context-ignore",
+            "# generated sources\n\ngenerated/\n  *.snap\n",
+        ),
+        ("src/lib.rs", "pub fn lib() {}\n"),
+        ("generated/bindings.rs", "// generated\n"),
+        ("tests/output.snap", "snapshot\n"),
+    ]);
+    let config = Config {
+        include_patterns: vec!["*.rs".to_string(), "*.snap".to_string()],
+        ..base_config(&dir)
+    };
+
+    assert_eq!(
+        load_ignore_file(dir.path()).unwrap(),
+        vec!["generated", "*.snap"]
+    );
+    assert_eq!(
+        sorted_paths(&discover(&dir, config.clone())),
+        vec!["src/lib.rs"]
+    );
+
+    let mut tree = TreeContext::new(config);
+    let tree_str = tree.build_tree_from_root().unwrap().tree_str.clone();
+    assert!(tree_str.contains("lib.rs"));
+    assert!(!tree_str.contains("generated"));
+    assert!(!tree_str.contains("output.snap"));
+}
+
+#[test]
+fn test_globs_match_relative_to_target_or_repo_root() {
+    let dir = setup_dir(&[
+        ("src/app/main.rs", "fn main() {}\n"),
+        ("src/app/deep/util.rs", "pub fn util() {}\n"),
+        ("src/app/notes.txt", "notes\n"),
+        ("src/other.rs", "pub fn other() {}\n"),
+    ]);
+    let root = dir.path().to_string_lossy().to_string();
+    let target_paths = |includes: &[&str], excludes: &[&str]| -> Vec<String> {
+        let config = Config {
+            target_paths: vec!["src/app".to_string()],
+            include_patterns: includes.iter().map(|p| p.to_string()).collect(),
+            exclude_patterns: excludes.iter().map(|p| p.to_string()).collect(),
+            ..base_config(&dir)
+        };
+        sorted_paths(&FileContext::from_target_paths(config, &root).unwrap())
+    };
+
+    // Relative to the target, without a `**/` prefix. Paths are stored relative to the root
+    assert_eq!(
+        target_paths(&["*.rs"], &[]),
+        vec!["src/app/deep/util.rs", "src/app/main.rs"]
+    );
+    assert_eq!(target_paths(&["main.rs"], &[]), vec!["src/app/main.rs"]);
+    // Relative to the repository root
+    assert_eq!(
+        target_paths(&["src/app/*.txt"], &[]),
+        vec!["src/app/notes.txt"]
+    );
+    assert_eq!(
+        target_paths(&["*.rs"], &["src/app/deep"]),
+        vec!["src/app/main.rs"]
+    );
+    assert_eq!(target_paths(&["*.rs"], &["deep"]), vec!["src/app/main.rs"]);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_symlinked_directories_and_cycles() {
+    use std::os::unix::fs::symlink;
+
+    let dir = setup_dir(&[
+        ("src/lib.rs", "pub fn lib() {}\n"),
+        ("shared/util.rs", "// util\n"),
+    ]);
+    // A link back to the root, and a second route into `shared`
+    symlink(dir.path(), dir.path().join("src/loop")).unwrap();
+    symlink(
+        dir.path().join("shared"),
+        dir.path().join("src/shared_link"),
+    )
+    .unwrap();
+
+    // Symlinked directories are skipped by default
+    assert_eq!(
+        sorted_paths(&discover(&dir, base_config(&dir))),
+        vec!["shared/util.rs", "src/lib.rs"]
+    );
+
+    // Followed, each real directory is still only walked once, so the loop terminates
+    let config = Config {
+        follow_symlinks: true,
+        ..base_config(&dir)
+    };
+    let paths = sorted_paths(&discover(&dir, config.clone()));
+    assert_eq!(paths.len(), 2);
+    assert!(paths.contains(&"src/lib.rs".to_string()));
+
+    let mut tree = TreeContext::new(config);
+    let tree_str = tree.build_tree_from_root().unwrap().tree_str.clone();
+    // The tree lists the discovered files, so the loop adds nothing to it either
+    assert!(!tree_str.contains("loop"));
+    assert_eq!(tree_str.matches("lib.rs").count(), 1);
+    assert_eq!(tree_str.matches("util.rs").count(), 1);
+}
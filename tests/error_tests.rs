@@ -0,0 +1,132 @@
+//===----------------------------------------------------------------------===//
+//
+// Copyright (c) 2025 David Rivera
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+//
+// SPDX-License-Identifier: MIT
+//
+//===----------------------------------------------------------------------===//
+//
+// Integration tests for the failure modes reported through ContextError
+//===----------------------------------------------------------------------===//
+//
+
+use rusty_repo_context_manager::{
+    Config, ContextError, ContextManager, FileContext, OutputContext, OutputDestination,
+    RepositoryContext,
+};
+use std::fs;
+use tempfile::TempDir;
+
+fn config_for(dir: &TempDir) -> Config {
+    Config {
+        root_path: dir.path().to_string_lossy().to_string(),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_missing_repository_is_a_git_error() {
+    let dir = TempDir::new().unwrap();
+    let mut manager = ContextManager::new(config_for(&dir));
+
+    match manager.build_context() {
+        Err(ContextError::Git(e)) => assert_eq!(e.code(), git2::ErrorCode::NotFound),
+        other => panic!("expected a git error, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_invalid_glob_is_a_glob_error() {
+    let dir = TempDir::new().unwrap();
+    let config = Config {
+        exclude_patterns: vec!["src/[".into()],
+        ..config_for(&dir)
+    };
+
+    let root = dir.path().to_str().unwrap();
+    let result = FileContext::discover_files(root, root, &config);
+    assert!(matches!(result, Err(ContextError::Glob(_))));
+    let result = FileContext::from_root(config, root);
+    assert!(matches!(result, Err(ContextError::Glob(_))));
+}
+
+#[test]
+fn test_target_outside_root_is_rejected() {
+    let root = TempDir::new().unwrap();
+    let elsewhere = TempDir::new().unwrap();
+    let outside = elsewhere.path().join("secret.txt");
+    fs::write(&outside, "nope\n").unwrap();
+
+    let config = Config {
+        target_paths: vec![outside.to_string_lossy().to_string()],
+        ..config_for(&root)
+    };
+
+    match FileContext::from_target_paths(config, root.path().to_str().unwrap()) {
+        Err(ContextError::PathOutsideRoot(path)) => assert_eq!(path, outside),
+        other => panic!("expected PathOutsideRoot, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_target_escaping_root_through_parent_dirs_is_rejected() {
+    let parent = TempDir::new().unwrap();
+    let root = parent.path().join("repo");
+    fs::create_dir_all(root.join("src")).unwrap();
+    fs::write(parent.path().join("secret.txt"), "nope\n").unwrap();
+    fs::create_dir(parent.path().join("private")).unwrap();
+    fs::write(parent.path().join("private/key.pem"), "nope\n").unwrap();
+    let root_str = root.to_string_lossy().to_string();
+
+    for target in ["src/../../secret.txt", "../private"] {
+        let config = Config {
+            root_path: root_str.clone(),
+            target_paths: vec![target.to_string()],
+            ..Default::default()
+        };
+        match FileContext::from_target_paths(config, &root_str) {
+            Err(ContextError::PathOutsideRoot(path)) => assert!(path.ends_with(target)),
+            other => panic!("expected PathOutsideRoot for {}, got {:?}", target, other),
+        }
+    }
+
+    // Opting in packages the file under its resolved absolute path
+    let config = Config {
+        root_path: root_str.clone(),
+        target_paths: vec!["src/../../secret.txt".to_string()],
+        allow_outside_root: true,
+        ..Default::default()
+    };
+    let ctx = FileContext::from_target_paths(config, &root_str).unwrap();
+    assert_eq!(ctx.file_entries.len(), 1);
+    assert!(ctx.file_entries[0].path.ends_with("/secret.txt"));
+    assert!(!ctx.file_entries[0].path.contains(".."));
+}
+
+#[test]
+fn test_unwritable_output_is_an_output_creation_error() {
+    let dir = TempDir::new().unwrap();
+    fs::write(dir.path().join("main.rs"), "fn main() {}\n").unwrap();
+
+    let mut manager = ContextManager::new(config_for(&dir));
+    manager.context = Some(RepositoryContext {
+        root_path: dir.path().to_string_lossy().to_string(),
+        file_ctx: FileContext::from_root(config_for(&dir), dir.path().to_str().unwrap()).unwrap(),
+        ..Default::default()
+    });
+
+    let missing_dir = dir.path().join("no/such/dir/context");
+    let result = OutputContext::new(manager)
+        .destination(OutputDestination::File(
+            missing_dir.to_string_lossy().to_string(),
+        ))
+        .generate();
+
+    match result {
+        Err(ContextError::OutputCreation { path, .. }) => assert!(path.ends_with("context.md")),
+        other => panic!("expected OutputCreation, got {:?}", other),
+    }
+}
@@ -39,6 +39,7 @@ fn exclude_glob_filters_out_matches() {
         is_recursive: true,
         recent_only: false,
         show_line_numbers: false,
+        ..Default::default()
     };
 
     let file_ctx = FileContext::from_root(config.clone(), &config.root_path).unwrap();
@@ -65,6 +66,7 @@ fn include_glob_only_includes_matches() {
         is_recursive: true,
         recent_only: false,
         show_line_numbers: false,
+        ..Default::default()
     };
 
     let file_ctx = FileContext::from_root(config.clone(), &config.root_path).unwrap();
@@ -93,6 +95,7 @@ fn include_and_exclude_combined() {
         is_recursive: true,
         recent_only: false,
         show_line_numbers: false,
+        ..Default::default()
     };
 
     let file_ctx = FileContext::from_root(config.clone(), &config.root_path).unwrap();
@@ -39,6 +39,7 @@ fn test_line_counting() {
         is_recursive: false,
         recent_only: false,
         show_line_numbers: false,
+        ..Default::default()
     };
 
     let file_context = FileContext::from_root(config, temp_dir.path().to_str().unwrap())
@@ -219,6 +219,7 @@ fn test_file_with_only_newlines() {
         is_recursive: false,
         recent_only: false,
         show_line_numbers: false,
+        ..Default::default()
     };
 
     let file_context = FileContext::from_root(config, temp_dir.path().to_str().unwrap())
@@ -254,6 +255,7 @@ fn test_recursive_directory_line_counting() {
         is_recursive: true,
         recent_only: false,
         show_line_numbers: false,
+        ..Default::default()
     };
 
     let file_context = FileContext::from_root(config, temp_dir.path().to_str().unwrap())
@@ -284,6 +286,7 @@ fn test_summary_generation() {
         is_recursive: false,
         recent_only: false,
         show_line_numbers: false,
+        ..Default::default()
     };
 
     let file_context = FileContext::from_root(config, temp_dir.path().to_str().unwrap())
@@ -157,6 +157,7 @@ mod output_context_tests {
             is_recursive: true,
             recent_only: false,
             show_line_numbers: false,
+            ..Default::default()
         };
 
         let mut manager = ContextManager::new(config);
@@ -178,6 +179,7 @@ mod output_context_tests {
             is_recursive: true,
             recent_only: false,
             show_line_numbers: false,
+            ..Default::default()
         };
 
         let mut manager = ContextManager::new(config);
@@ -204,6 +206,7 @@ mod output_context_tests {
             is_recursive: true,
             recent_only: false,
             show_line_numbers: false,
+            ..Default::default()
         };
 
         let mut manager = ContextManager::new(config);
@@ -242,6 +245,7 @@ mod output_context_tests {
             is_recursive: true,
             recent_only: false,
             show_line_numbers: false,
+            ..Default::default()
         };
 
         let mut manager = ContextManager::new(config);
@@ -274,6 +278,7 @@ mod output_context_tests {
             is_recursive: true,
             recent_only: false,
             show_line_numbers: false,
+            ..Default::default()
         };
 
         let mut manager = ContextManager::new(config);
@@ -326,6 +331,7 @@ mod output_context_tests {
             is_recursive: true,
             recent_only: false,
             show_line_numbers: false,
+            ..Default::default()
         };
 
         let mut manager = ContextManager::new(config);
@@ -355,6 +361,7 @@ mod output_context_tests {
             is_recursive: true,
             recent_only: false,
             show_line_numbers: false,
+            ..Default::default()
         };
 
         let mut manager = ContextManager::new(config);
@@ -393,6 +400,7 @@ mod output_context_tests {
             is_recursive: true,
             recent_only: false,
             show_line_numbers: false,
+            ..Default::default()
         };
 
         let mut manager = ContextManager::new(config);
@@ -435,6 +443,7 @@ mod output_context_tests {
             is_recursive: true,
             recent_only: false,
             show_line_numbers: false,
+            ..Default::default()
         };
 
         let mut manager = ContextManager::new(config);
@@ -470,6 +479,7 @@ mod integration_tests {
             is_recursive: true,
             recent_only: false,
             show_line_numbers: false,
+            ..Default::default()
         };
 
         let mut manager = ContextManager::new(config);
@@ -517,6 +527,7 @@ mod integration_tests {
             is_recursive: true,
             recent_only: false,
             show_line_numbers: false,
+            ..Default::default()
         };
 
         // We need separate managers since generate() consumes the context
@@ -555,6 +566,7 @@ mod integration_tests {
             is_recursive: true,
             recent_only: false,
             show_line_numbers: false,
+            ..Default::default()
         };
 
         let mut manager = ContextManager::new(config);
@@ -36,6 +36,7 @@ fn test_summary_calculation() {
         is_recursive: false,
         recent_only: false,
         show_line_numbers: false,
+        ..Default::default()
     };
 
     let file_context = FileContext::from_root(config, temp_dir.path().to_str().unwrap())
@@ -50,3 +51,51 @@ fn test_summary_calculation() {
     let total_size: u64 = file_context.file_entries.iter().map(|f| f.size).sum();
     assert!(total_size > 0);
 }
+
+#[test]
+fn test_primary_language_in_summary() {
+    use rusty_repo_context_manager::types::{GitInfo, RepositoryContext};
+    use rusty_repo_context_manager::{ContextManager, OutputContext, OutputDestination};
+
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    fs::write(temp_dir.path().join("main.rs"), "fn main() {\n}\n\n").unwrap(); // 3 lines
+    fs::write(temp_dir.path().join("lib.rs"), "pub mod a;\npub mod b;\n").unwrap(); // 2 lines
+    fs::write(temp_dir.path().join("notes.md"), "# Notes\n").unwrap(); // 1 line
+
+    let config = Config {
+        root_path: temp_dir.path().to_string_lossy().to_string(),
+        is_recursive: false,
+        ..Default::default()
+    };
+    let file_ctx = FileContext::from_root(config.clone(), temp_dir.path().to_str().unwrap())
+        .expect("Failed to create FileContext");
+
+    let mut manager = ContextManager::new(config);
+    manager.context = Some(RepositoryContext {
+        root_path: temp_dir.path().to_string_lossy().to_string(),
+        git_info: GitInfo {
+            is_repo: false,
+            commit_hash: None,
+            branch: None,
+            author: None,
+            email: None,
+            date: None,
+            ..Default::default()
+        },
+        file_ctx,
+        tree_repr: String::new(),
+        ..Default::default()
+    });
+
+    let output_base = temp_dir.path().join("summary");
+    OutputContext::new(manager)
+        .destination(OutputDestination::File(
+            output_base.to_string_lossy().to_string(),
+        ))
+        .generate()
+        .expect("Failed to generate output");
+
+    let output = fs::read_to_string(format!("{}.md", output_base.to_string_lossy())).unwrap();
+    let summary = output.split("## Summary\n\n").nth(1).unwrap();
+    assert!(summary.starts_with("Primary language: Rust (83% of lines)\n"));
+}
@@ -44,16 +44,36 @@ mod tests {
     fn test_output_destination_variants() {
         let stdout_dest = OutputDestination::Stdout;
         let file_dest = OutputDestination::File("test.txt".to_string());
+        let both_dest = OutputDestination::Both("test.txt".to_string());
+        let clipboard_dest = OutputDestination::Clipboard;
 
         // Test that we can match on variants
         match stdout_dest {
             OutputDestination::Stdout => { /* Expected */ }
-            OutputDestination::File(_) => panic!("Expected Stdout"),
+            OutputDestination::File(_)
+            | OutputDestination::Both(_)
+            | OutputDestination::Clipboard => panic!("Expected Stdout"),
         }
 
         match file_dest {
             OutputDestination::File(path) => assert_eq!(path, "test.txt"),
-            OutputDestination::Stdout => panic!("Expected File"),
+            OutputDestination::Stdout
+            | OutputDestination::Both(_)
+            | OutputDestination::Clipboard => panic!("Expected File"),
+        }
+
+        match both_dest {
+            OutputDestination::Both(path) => assert_eq!(path, "test.txt"),
+            OutputDestination::Stdout
+            | OutputDestination::File(_)
+            | OutputDestination::Clipboard => panic!("Expected Both"),
+        }
+
+        match clipboard_dest {
+            OutputDestination::Clipboard => { /* Expected */ }
+            OutputDestination::Stdout | OutputDestination::File(_) | OutputDestination::Both(_) => {
+                panic!("Expected Clipboard")
+            }
         }
     }
 
@@ -51,6 +51,7 @@ fn test_build_tree_from_root_basic() {
         output_file: None,
         recent_only: false,
         show_line_numbers: false,
+        ..Default::default()
     };
 
     let mut tree_context = TreeContext::new(config);
@@ -80,6 +81,7 @@ fn test_build_tree_from_root_with_exclude_patterns() {
         output_file: None,
         recent_only: false,
         show_line_numbers: false,
+        ..Default::default()
     };
 
     let mut tree_context = TreeContext::new(config);
@@ -109,6 +111,7 @@ fn test_build_tree_from_root_with_include_patterns() {
         output_file: None,
         recent_only: false,
         show_line_numbers: false,
+        ..Default::default()
     };
 
     let mut tree_context = TreeContext::new(config);
@@ -138,6 +141,7 @@ fn test_build_tree_from_targets_with_specific_files() {
         output_file: None,
         recent_only: false,
         show_line_numbers: false,
+        ..Default::default()
     };
 
     let mut tree_context = TreeContext::new(config);
@@ -168,6 +172,7 @@ fn test_build_tree_from_targets_with_directory() {
         output_file: None,
         recent_only: false,
         show_line_numbers: false,
+        ..Default::default()
     };
 
     let mut tree_context = TreeContext::new(config);
@@ -200,6 +205,7 @@ fn test_build_tree_from_targets_root_directory_detection() {
         output_file: None,
         recent_only: false,
         show_line_numbers: false,
+        ..Default::default()
     };
 
     let mut tree_context = TreeContext::new(config);
@@ -232,6 +238,7 @@ fn test_build_tree_from_targets_with_absolute_path() {
         output_file: None,
         recent_only: false,
         show_line_numbers: false,
+        ..Default::default()
     };
 
     let mut tree_context = TreeContext::new(config);
@@ -259,6 +266,7 @@ fn test_empty_target_paths_falls_back_to_full_tree() {
         output_file: None,
         recent_only: false,
         show_line_numbers: false,
+        ..Default::default()
     };
 
     let mut tree_context = TreeContext::new(config);
@@ -283,6 +291,7 @@ fn test_tree_context_new() {
         output_file: Some("output.md".to_string()),
         recent_only: false,
         show_line_numbers: false,
+        ..Default::default()
     };
 
     let tree_context = TreeContext::new(config.clone());
@@ -306,6 +315,7 @@ fn test_nonexistent_target_paths() {
         output_file: None,
         recent_only: false,
         show_line_numbers: false,
+        ..Default::default()
     };
 
     let mut tree_context = TreeContext::new(config);
@@ -317,3 +327,125 @@ fn test_nonexistent_target_paths() {
     // Tree should contain at least the root
     assert!(!tree_str.is_empty());
 }
+
+#[test]
+fn test_tree_max_children_limits_breadth() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    let root = temp_dir.path();
+
+    fs::create_dir_all(root.join("many/sub")).unwrap();
+    fs::write(root.join("many/sub/inner.rs"), "// inner").unwrap();
+    for i in 0..12 {
+        fs::write(root.join(format!("many/file{:02}.rs", i)), "// file").unwrap();
+    }
+
+    let config = Config {
+        root_path: root.to_string_lossy().to_string(),
+        tree_max_children: Some(5),
+        ..Default::default()
+    };
+
+    let mut tree_context = TreeContext::new(config);
+    let _ = tree_context.build_tree_from_root().unwrap();
+    let tree_str = &tree_context.tree_str;
+
+    // The subdirectory is kept first, followed by four files and the marker
+    assert!(tree_str.contains("sub"));
+    assert!(tree_str.contains("inner.rs"));
+    let shown = (0..12)
+        .filter(|i| tree_str.contains(&format!("file{:02}.rs", i)))
+        .count();
+    assert_eq!(shown, 4);
+    assert!(tree_str.contains("... (8 more files) ..."));
+}
+
+#[test]
+fn test_max_depth_truncates_tree() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    let root = temp_dir.path();
+    fs::create_dir_all(root.join("level1/level2/level3/level4")).unwrap();
+    fs::write(root.join("top.txt"), "top").unwrap();
+    fs::write(root.join("level1/one.txt"), "1").unwrap();
+    fs::write(root.join("level1/level2/two.txt"), "2").unwrap();
+    fs::write(root.join("level1/level2/level3/three.txt"), "3").unwrap();
+    fs::write(root.join("level1/level2/level3/level4/four.txt"), "4").unwrap();
+
+    let config = Config {
+        root_path: root.to_string_lossy().to_string(),
+        is_recursive: true,
+        max_depth: Some(2),
+        ..Default::default()
+    };
+
+    let mut tree_context = TreeContext::new(config);
+    assert!(tree_context.build_tree_from_root().is_ok());
+
+    let tree_str = &tree_context.tree_str;
+    assert!(tree_str.contains("top.txt"));
+    assert!(tree_str.contains("level1"));
+    assert!(tree_str.contains("one.txt"));
+    assert!(tree_str.contains("level2"));
+    // level2 holds level3 and two.txt, which are no longer expanded
+    assert!(tree_str.contains("... (2 more) ..."));
+    assert!(!tree_str.contains("two.txt"));
+    assert!(!tree_str.contains("level3"));
+    assert!(!tree_str.contains("four.txt"));
+}
+
+#[test]
+fn test_ascii_tree_has_no_unicode() {
+    let temp_dir = create_test_directory_structure();
+    let config = Config {
+        root_path: temp_dir.path().to_string_lossy().to_string(),
+        is_recursive: true,
+        ascii_tree: true,
+        ..Default::default()
+    };
+
+    let mut tree_context = TreeContext::new(config.clone());
+    assert!(tree_context.build_tree_from_root().is_ok());
+
+    let tree_str = &tree_context.tree_str;
+    assert!(tree_str.is_ascii(), "{}", tree_str);
+    assert!(tree_str.contains("|-- "));
+    assert!(tree_str.contains("`-- "));
+    assert!(tree_str.contains("utils.rs"));
+
+    // The default rendering uses box-drawing characters
+    let mut tree_context = TreeContext::new(Config {
+        ascii_tree: false,
+        ..config
+    });
+    assert!(tree_context.build_tree_from_root().is_ok());
+    assert!(!tree_context.tree_str.is_ascii());
+}
+
+#[test]
+fn test_hidden_entries_in_tree() {
+    let temp_dir = create_test_directory_structure();
+    fs::create_dir_all(temp_dir.path().join(".config")).unwrap();
+    fs::write(temp_dir.path().join(".config/app.toml"), "debug = true").unwrap();
+    fs::create_dir_all(temp_dir.path().join(".git")).unwrap();
+    fs::write(temp_dir.path().join(".git/HEAD"), "ref: refs/heads/main").unwrap();
+
+    let build = |include_hidden: bool| {
+        let mut tree_context = TreeContext::new(Config {
+            root_path: temp_dir.path().to_string_lossy().to_string(),
+            is_recursive: true,
+            include_hidden,
+            ..Default::default()
+        });
+        assert!(tree_context.build_tree_from_root().is_ok());
+        tree_context.tree_str
+    };
+
+    let tree_str = build(false);
+    assert!(!tree_str.contains(".config"));
+    assert!(!tree_str.contains("app.toml"));
+    assert!(!tree_str.contains(".git"));
+
+    let tree_str = build(true);
+    assert!(tree_str.contains(".config"));
+    assert!(tree_str.contains("app.toml"));
+    assert!(!tree_str.contains(".git"));
+}